@@ -0,0 +1,242 @@
+use anyhow::{Result, anyhow};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+// Backups live in the destination so that files can be moved rather than
+// copied, one directory per restore named for when it happened
+const BACKUP_DIRECTORY: &str = ".select-save-backups";
+const MANIFEST: &str = "restore.json";
+
+// Undoing keeps the restored files here, inside the restore's directory, which
+// loses its manifest so that it's no longer offered for undo
+const UNDONE_DIRECTORY: &str = ".undone";
+
+// What a restore did: the files it moved aside and the files it copied in,
+// relative to the destination
+#[derive(Serialize, Deserialize)]
+pub struct Backup {
+    pub label: String,
+    pub moved: Vec<PathBuf>,
+    pub copied: Vec<PathBuf>,
+
+    #[serde(skip)]
+    destination: PathBuf,
+    #[serde(skip)]
+    directory: PathBuf,
+}
+
+impl Backup {
+    pub fn begin(destination: &Path, label: String) -> Result<Self> {
+        let backups = destination.join(BACKUP_DIRECTORY);
+        let timestamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+
+        let mut directory = backups.join(&timestamp);
+        let mut n = 2;
+        while directory.exists() {
+            directory = backups.join(format!("{timestamp}-{n}"));
+            n += 1;
+        }
+        std::fs::create_dir_all(&directory)?;
+
+        let backup = Self {
+            label,
+            moved: Vec::new(),
+            copied: Vec::new(),
+            destination: destination.to_path_buf(),
+            directory,
+        };
+        backup.save()?;
+        Ok(backup)
+    }
+
+    // The most recent restore that hasn't been undone
+    pub fn latest(destination: &Path) -> Result<Option<Self>> {
+        let backups = destination.join(BACKUP_DIRECTORY);
+        if !backups.exists() {
+            return Ok(None);
+        }
+
+        let mut directories = std::fs::read_dir(&backups)?
+            .filter_map(Result::ok)
+            .map(|e| e.path())
+            .filter(|p| p.join(MANIFEST).exists())
+            .collect::<Vec<_>>();
+        // Restores in the same second are numbered, e.g. 20240101-120000-10,
+        // which has to sort after 20240101-120000-9
+        directories.sort_by_key(|directory| {
+            let name = directory
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            match name.rsplit_once('-') {
+                Some((timestamp, n)) if timestamp.contains('-') => {
+                    (timestamp.to_string(), n.parse::<usize>().unwrap_or(0))
+                }
+                _ => (name, 1),
+            }
+        });
+
+        let Some(directory) = directories.pop() else {
+            return Ok(None);
+        };
+
+        let manifest = std::fs::read_to_string(directory.join(MANIFEST))?;
+        let mut backup: Self = serde_json::from_str(&manifest)?;
+        backup.destination = destination.to_path_buf();
+        backup.directory = directory;
+        Ok(Some(backup))
+    }
+
+    // The directory's name is the time of the restore
+    pub fn name(&self) -> String {
+        self.directory
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    fn relative<'a>(&self, file: &'a Path) -> Result<&'a Path> {
+        file.strip_prefix(&self.destination)
+            .map_err(|_| anyhow!("{file:?} is outside of {:?}", self.destination))
+    }
+
+    fn save(&self) -> Result<()> {
+        let manifest = serde_json::to_string_pretty(self)?;
+        std::fs::write(self.directory.join(MANIFEST), manifest)?;
+        Ok(())
+    }
+
+    // Instead of deleting or overwriting a file, move it into the backup
+    pub fn move_aside(&mut self, file: &Path) -> Result<()> {
+        let relative = self.relative(file)?.to_path_buf();
+        let target = self.directory.join(&relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        info!("Backing up {file:?} into {target:?}");
+        std::fs::rename(file, &target)?;
+        self.moved.push(relative);
+        self.save()
+    }
+
+    pub fn copy(&mut self, source: &Path, destination: &Path) -> Result<()> {
+        if destination.exists() {
+            self.move_aside(destination)?;
+        }
+
+        info!("Copying {source:?} into {destination:?}");
+        std::fs::copy(source, destination)?;
+        self.copied.push(self.relative(destination)?.to_path_buf());
+        self.save()
+    }
+
+    // Moves whatever is at the restored paths aside rather than removing it,
+    // since the game may have been played since, including files the emulator
+    // wrote after the restore, and puts the moved files back where they were
+    pub fn undo(self) -> Result<Vec<String>> {
+        let mut results = Vec::new();
+
+        let undone = self.directory.join(UNDONE_DIRECTORY);
+        let mut kept = Vec::new();
+        for relative in self.copied.iter().chain(&self.moved) {
+            let file = self.destination.join(relative);
+            if kept.contains(&relative) || !file.exists() {
+                continue;
+            }
+
+            let target = undone.join(relative);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            info!("Keeping {file:?} in {target:?}");
+            std::fs::rename(&file, &target)?;
+            kept.push(relative);
+            results.push(format!(
+                "Kept {:?} in {BACKUP_DIRECTORY}/{}",
+                file.file_name().unwrap(),
+                self.name()
+            ));
+        }
+
+        for relative in &self.moved {
+            let file = self.destination.join(relative);
+            info!("Putting back {file:?}");
+            std::fs::rename(self.directory.join(relative), &file)?;
+            results.push(format!("Put back {:?}", file.file_name().unwrap()));
+        }
+
+        std::fs::remove_file(self.directory.join(MANIFEST))?;
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn destination(name: &str) -> PathBuf {
+        let destination = std::env::temp_dir()
+            .join(format!("select-save-{}", std::process::id()))
+            .join(name);
+        let _ = std::fs::remove_dir_all(&destination);
+        std::fs::create_dir_all(&destination).unwrap();
+        destination
+    }
+
+    #[test]
+    fn undo_keeps_files_written_since_the_restore() {
+        let destination = destination("undo");
+        let (srm, state) = (
+            destination.join("Game.srm"),
+            destination.join("Game.state.auto"),
+        );
+        let library = destination.join("library.state");
+        std::fs::write(&srm, "old sram").unwrap();
+        std::fs::write(&library, "restored state").unwrap();
+
+        let mut backup = Backup::begin(&destination, "library.state".to_string()).unwrap();
+        backup.move_aside(&srm).unwrap();
+        backup.copy(&library, &state).unwrap();
+
+        // Playing after the restore writes new SRAM and updates the state
+        std::fs::write(&srm, "new sram").unwrap();
+        std::fs::write(&state, "played state").unwrap();
+
+        let backup = Backup::latest(&destination).unwrap().unwrap();
+        let undone = backup.directory.join(UNDONE_DIRECTORY);
+        backup.undo().unwrap();
+
+        assert_eq!(std::fs::read_to_string(&srm).unwrap(), "old sram");
+        assert!(!state.exists());
+        assert_eq!(
+            std::fs::read_to_string(undone.join("Game.srm")).unwrap(),
+            "new sram"
+        );
+        assert_eq!(
+            std::fs::read_to_string(undone.join("Game.state.auto")).unwrap(),
+            "played state"
+        );
+        assert!(Backup::latest(&destination).unwrap().is_none());
+    }
+
+    #[test]
+    fn latest_is_the_most_recent_restore_in_the_same_second() {
+        let destination = destination("latest");
+        let backups = destination.join(BACKUP_DIRECTORY);
+        for name in ["20240101-120000", "20240101-120000-2", "20240101-120000-10"] {
+            std::fs::create_dir_all(backups.join(name)).unwrap();
+            std::fs::write(
+                backups.join(name).join(MANIFEST),
+                format!(r#"{{"label": "{name}", "moved": [], "copied": []}}"#),
+            )
+            .unwrap();
+        }
+
+        let latest = Backup::latest(&destination).unwrap().unwrap();
+        assert_eq!(latest.name(), "20240101-120000-10");
+    }
+}
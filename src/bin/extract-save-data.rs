@@ -1,31 +1,287 @@
-use anyhow::Result;
-use clap::Parser;
-use select_save::extractor::Extractor;
-use std::path::PathBuf;
+use anyhow::{Result, anyhow};
+use clap::{Parser, ValueEnum};
+use itertools::{EitherOrBoth, Itertools};
+use select_save::extractor::{Extraction, Extractor, Field, Validity, diff};
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use walkdir::WalkDir;
 
 #[derive(Parser, Debug)]
 struct Args {
-    #[arg(long)]
-    file: PathBuf,
+    #[arg(long, required_unless_present_any = ["lint", "check", "diff"])]
+    file: Option<PathBuf>,
+
+    #[arg(long, required_unless_present = "diff")]
+    config: Option<PathBuf>,
 
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    // Report problems in the config instead of extracting, checking reads
+    // against --file when given
     #[arg(long)]
-    config: PathBuf,
+    lint: bool,
+
+    // Compare each save in the directory against the output stored next to it
+    // in a .expected file, e.g. foo.srm and foo.srm.expected
+    #[arg(long, conflicts_with_all = ["file", "lint"])]
+    check: Option<PathBuf>,
+
+    // List the bytes that differ between two saves, labeled with the fields
+    // they're part of when --config is given
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["BEFORE", "AFTER"],
+        conflicts_with_all = ["file", "lint", "check"]
+    )]
+    diff: Option<Vec<PathBuf>>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    Text,
+    Json,
 }
 
 fn main() -> Result<()> {
     tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .with(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
     let args = Args::parse();
 
-    let extractor = Extractor::new(&args.config)?;
+    let extractor = args.config.as_deref().map(Extractor::new).transpose()?;
 
-    for result in extractor.extract(&args.file)? {
-        println!("{result}")
+    if let Some(saves) = &args.diff {
+        return print_diff(extractor.as_ref(), &saves[0], &saves[1]);
+    }
+
+    let config = args.config.ok_or_else(|| anyhow!("--config is required"))?;
+    let extractor = extractor.unwrap();
+
+    if args.lint {
+        let problems = extractor.lint(args.file.as_deref())?;
+        for problem in &problems {
+            println!("{problem}");
+        }
+
+        if !problems.is_empty() {
+            anyhow::bail!("Found {} problems in {:?}", problems.len(), config);
+        }
+        return Ok(());
+    }
+
+    if let Some(directory) = &args.check {
+        return check(&extractor, directory);
+    }
+
+    let file = args.file.ok_or_else(|| anyhow!("--file is required"))?;
+    let extraction = extractor.extract(&file)?;
+
+    match args.format {
+        Format::Text => print!("{}", render_text(&extraction)),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&extraction)?),
+    }
+
+    if extraction.validity == Validity::Corrupt {
+        anyhow::bail!("Checksum mismatch in {file:?}");
     }
 
     Ok(())
 }
+
+fn render_text(extraction: &Extraction) -> String {
+    let mut text = String::new();
+    for group in &extraction.groups {
+        match &group.title {
+            Some(title) => {
+                writeln!(text, "[{title}]").unwrap();
+                for result in &group.lines {
+                    writeln!(text, "  {}", result.text).unwrap();
+                }
+            }
+            None => {
+                for result in &group.lines {
+                    writeln!(text, "{}", result.text).unwrap();
+                }
+            }
+        }
+    }
+    text
+}
+
+// Expected files hold exactly what extract-save-data prints for the save, so
+// they can be created with extract-save-data --file foo.srm > foo.srm.expected
+fn check(extractor: &Extractor, directory: &Path) -> Result<()> {
+    let mut passed = 0;
+    let mut failed = 0;
+
+    for entry in WalkDir::new(directory).sort_by_file_name() {
+        let path = entry?.into_path();
+        if path.extension().is_none_or(|e| e != "expected") {
+            continue;
+        }
+
+        let save = path.with_extension("");
+        match check_save(extractor, &save, &path) {
+            Ok(differences) if differences.is_empty() => {
+                passed += 1;
+                println!("ok   {}", save.display());
+            }
+            Ok(differences) => {
+                failed += 1;
+                println!("FAIL {}", save.display());
+                for difference in differences {
+                    println!("     {difference}");
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                println!("FAIL {}", save.display());
+                println!("     {e}");
+            }
+        }
+    }
+
+    println!("{passed} passed, {failed} failed");
+
+    if passed + failed == 0 {
+        anyhow::bail!("No .expected files in {directory:?}");
+    }
+    if failed > 0 {
+        anyhow::bail!("{failed} saves did not match their expected output");
+    }
+
+    Ok(())
+}
+
+fn check_save(extractor: &Extractor, save: &Path, expected: &Path) -> Result<Vec<String>> {
+    if !save.exists() {
+        anyhow::bail!("No save next to {expected:?}");
+    }
+
+    let expected = std::fs::read_to_string(expected)?;
+    let extraction = extractor.extract(save)?;
+    let actual = render_text(&extraction);
+
+    let mut differences = Vec::new();
+    // The rendered text doesn't mention the checksum, so a save that's turned
+    // corrupt would otherwise still pass
+    if extraction.validity == Validity::Corrupt {
+        differences.push("checksum mismatch".to_string());
+    }
+
+    differences.extend(
+        expected
+            .lines()
+            .zip_longest(actual.lines())
+            .enumerate()
+            .filter_map(|(i, pair)| {
+                let line = i + 1;
+                match pair {
+                    EitherOrBoth::Both(e, a) if e == a => None,
+                    EitherOrBoth::Both(e, a) => {
+                        Some(format!("line {line}: expected {e:?}, got {a:?}"))
+                    }
+                    EitherOrBoth::Left(e) => {
+                        Some(format!("line {line}: expected {e:?}, got nothing"))
+                    }
+                    EitherOrBoth::Right(a) => Some(format!("line {line}: unexpected {a:?}")),
+                }
+            }),
+    );
+    Ok(differences)
+}
+
+// Fields from both saves are labeled with their slot, if any, and paired up by
+// name and location so that their values can be shown side by side
+fn print_diff(extractor: Option<&Extractor>, before: &Path, after: &Path) -> Result<()> {
+    let changes = diff(before, after)?;
+    let fields = |save| -> Result<Vec<(String, Field)>> {
+        let Some(extractor) = extractor else {
+            return Ok(Vec::new());
+        };
+        Ok(extractor
+            .extract(save)?
+            .groups
+            .into_iter()
+            .flat_map(|group| {
+                let label = match &group.title {
+                    Some(title) => format!("[{title}] "),
+                    None => String::new(),
+                };
+                group
+                    .lines
+                    .into_iter()
+                    .flat_map(|line| line.fields)
+                    .map(move |field| (format!("{label}{}", field.name), field))
+            })
+            .collect())
+    };
+    let (fields_before, fields_after) = (fields(before)?, fields(after)?);
+
+    for change in &changes {
+        let (start, end) = (*change.range.start(), *change.range.end());
+        if start == end {
+            println!("0x{start:X}");
+        } else {
+            println!("0x{start:X}~0x{end:X} ({} bytes)", end - start + 1);
+        }
+        println!("  - {}", hex(&change.before));
+        println!("  + {}", hex(&change.after));
+
+        let overlaps = |field: &Field| {
+            field
+                .bytes
+                .iter()
+                .any(|bytes| *bytes.start() <= end && start <= *bytes.end())
+        };
+        let mut labeled = Vec::new();
+        for (label, field) in fields_before.iter().chain(&fields_after) {
+            if !overlaps(field) || labeled.contains(&label) {
+                continue;
+            }
+            labeled.push(label);
+
+            let value = |fields: &[(String, Field)]| {
+                fields
+                    .iter()
+                    .find(|(l, f)| l == label && f.bytes == field.bytes)
+                    .map_or("(not shown)".to_string(), |(_, f)| f.formatted.clone())
+            };
+            println!(
+                "  {label}: {} -> {}",
+                value(&fields_before),
+                value(&fields_after)
+            );
+        }
+    }
+
+    println!("{} changed ranges", changes.len());
+    Ok(())
+}
+
+// Long changes are abbreviated, since a whole block can change at once
+fn hex(bytes: &[u8]) -> String {
+    const MAX_BYTES: usize = 16;
+
+    if bytes.is_empty() {
+        return "(none)".to_string();
+    }
+
+    let shown = bytes
+        .iter()
+        .take(MAX_BYTES)
+        .map(|b| format!("{b:02X}"))
+        .join(" ");
+    if bytes.len() > MAX_BYTES {
+        format!("{shown} ...")
+    } else {
+        shown
+    }
+}
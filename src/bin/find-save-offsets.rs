@@ -0,0 +1,55 @@
+use anyhow::{Result, anyhow};
+use clap::Parser;
+use select_save::extractor::find_offsets;
+use std::path::PathBuf;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+// Finds where a value is stored by searching saves whose value is known, e.g.
+// find-save-offsets 1.srm=1200 2.srm=1550
+#[derive(Parser, Debug)]
+struct Args {
+    // Each save and the value it holds, as SAVE=VALUE
+    #[arg(required = true, value_parser = parse_save)]
+    saves: Vec<(PathBuf, i64)>,
+
+    #[arg(long, default_value_t = 100)]
+    max: usize,
+}
+
+fn parse_save(arg: &str) -> Result<(PathBuf, i64)> {
+    let (save, value) = arg
+        .rsplit_once('=')
+        .ok_or_else(|| anyhow!("Expected SAVE=VALUE, got {arg:?}"))?;
+    let value = value
+        .parse()
+        .map_err(|_| anyhow!("Invalid value {value:?}"))?;
+    Ok((PathBuf::from(save), value))
+}
+
+fn main() -> Result<()> {
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let args = Args::parse();
+
+    let saves = args
+        .saves
+        .iter()
+        .map(|(save, value)| (save.as_path(), *value))
+        .collect::<Vec<_>>();
+    let candidates = find_offsets(&saves)?;
+
+    for candidate in candidates.iter().take(args.max) {
+        println!("{candidate}");
+    }
+
+    match candidates.len() {
+        0 => anyhow::bail!("No offset holds those values in every save"),
+        n if n > args.max => println!("... and {} more; add saves to narrow it down", n - args.max),
+        _ => {}
+    }
+
+    Ok(())
+}
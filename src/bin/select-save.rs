@@ -1,24 +1,25 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use clap::Parser;
 use gag::Gag;
 use select_save::{
     manager,
+    restore::RestorePlan,
     scene::selectgame::{Operation, SelectGame},
     ui,
 };
 use std::os::unix::process::CommandExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tracing::{error, info};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[derive(Parser, Debug)]
 struct Args {
-    #[arg(long, env = "SCREEN_WIDTH")]
-    width: u32,
+    #[arg(long, env = "SCREEN_WIDTH", required_unless_present = "dry_run")]
+    width: Option<u32>,
 
-    #[arg(long, env = "SCREEN_HEIGHT")]
-    height: u32,
+    #[arg(long, env = "SCREEN_HEIGHT", required_unless_present = "dry_run")]
+    height: Option<u32>,
 
     #[arg(long)]
     root: PathBuf,
@@ -34,6 +35,11 @@ struct Args {
         default_value = "/usr/share/fonts/truetype/noto/NotoMono-Regular.ttf"
     )]
     font: PathBuf,
+
+    // Print what restoring this save from the library would remove and copy,
+    // without changing anything or starting the UI
+    #[arg(long, value_name = "SAVE")]
+    dry_run: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
@@ -49,8 +55,17 @@ fn main() -> Result<()> {
         height,
         width,
         font,
+        dry_run,
     } = Args::parse();
 
+    if let Some(save) = dry_run {
+        return print_plan(&root, &destination, &save);
+    }
+
+    let (Some(width), Some(height)) = (width, height) else {
+        anyhow::bail!("--width and --height are required");
+    };
+
     info!("Launching SDL {width}x{height}");
 
     // Gag stdout to suppress driver output
@@ -72,3 +87,23 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+// Games are directories three levels into the library, e.g.
+// snes/English/Chrono Trigger, with saves anywhere inside them
+fn print_plan(root: &Path, destination: &Path, save: &Path) -> Result<()> {
+    let relative = save
+        .strip_prefix(root)
+        .map_err(|_| anyhow!("{save:?} is outside of {root:?}"))?;
+    if relative.components().count() <= 3 {
+        anyhow::bail!("{save:?} is not inside a game's directory");
+    }
+    let game = root.join(relative.iter().take(3).collect::<PathBuf>());
+
+    let plan = RestorePlan::new(&game, root, destination, save)?;
+    for file in &plan.remove {
+        println!("remove {}", file.display());
+    }
+    println!("copy {} -> {}", plan.save.display(), plan.target.display());
+
+    Ok(())
+}
@@ -68,7 +68,10 @@ impl Cursor {
             self.index as isize + self.scroll_off as isize - self.page_size as isize + 1
         };
 
-        self.first = min(max(first, 0) as usize, self.len - self.page_size);
+        self.first = min(
+            max(first, 0) as usize,
+            self.len.saturating_sub(self.page_size),
+        );
         self.last = min(self.first + self.page_size, self.len) - 1;
     }
 
@@ -88,6 +91,12 @@ impl Cursor {
         self.go(-(self.page_size as isize));
     }
 
+    pub fn select(&mut self, index: usize) {
+        if index < self.len {
+            self.go(index as isize - self.index as isize);
+        }
+    }
+
     pub fn iter<I>(&self, iter: impl Iterator<Item = I>) -> impl Iterator<Item = (bool, I)> {
         let min = self.first;
         let current = self.index - min;
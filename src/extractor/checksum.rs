@@ -0,0 +1,127 @@
+use super::{
+    decode::{Encoding, Endian},
+    parse_number,
+};
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use tracing::{debug, error};
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum Algorithm {
+    Sum8,
+    Sum16,
+    Crc32,
+    Xor,
+}
+
+impl Algorithm {
+    fn width(&self) -> usize {
+        match self {
+            Algorithm::Sum8 | Algorithm::Xor => 1,
+            Algorithm::Sum16 => 2,
+            Algorithm::Crc32 => 4,
+        }
+    }
+
+    fn compute(&self, bytes: &[u8]) -> u32 {
+        match self {
+            Algorithm::Sum8 => bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) as u32,
+            Algorithm::Sum16 => bytes
+                .iter()
+                .fold(0u16, |sum, b| sum.wrapping_add(*b as u16))
+                as u32,
+            Algorithm::Xor => bytes.iter().fold(0u8, |x, b| x ^ b) as u32,
+            Algorithm::Crc32 => crc32(bytes),
+        }
+    }
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB88320 & mask);
+        }
+    }
+    !crc
+}
+
+#[derive(Deserialize, Debug)]
+pub(super) struct ChecksumConfig {
+    #[serde(rename = "type")]
+    algorithm: Algorithm,
+    range: String,
+    stored: String,
+    #[serde(default = "default_endian")]
+    endian: Endian,
+}
+
+fn default_endian() -> Endian {
+    Endian::Little
+}
+
+#[derive(Debug)]
+pub(super) struct Checksum {
+    algorithm: Algorithm,
+    start: usize,
+    end: usize,
+    stored: usize,
+    endian: Endian,
+}
+
+impl Checksum {
+    // Addresses are shifted by delta when the checksum comes from an included
+    // config
+    pub(super) fn new(config: &ChecksumConfig, delta: i64) -> Result<Self> {
+        let address = |s: &str| {
+            parse_number(s.trim())
+                .and_then(|n| n.checked_add(delta))
+                .and_then(|n| usize::try_from(n).ok())
+                .ok_or_else(|| anyhow!("Invalid checksum address {s:?}"))
+        };
+
+        let (start, end) = config
+            .range
+            .split_once('~')
+            .ok_or_else(|| anyhow!("Invalid checksum range {:?}", config.range))?;
+
+        Ok(Self {
+            algorithm: config.algorithm,
+            start: address(start)?,
+            end: address(end)?,
+            stored: address(&config.stored)?,
+            endian: config.endian,
+        })
+    }
+
+    pub(super) fn verify(&self, content: &[u8]) -> bool {
+        let Some(bytes) = content.get(self.start..=self.end) else {
+            error!(
+                "Checksum range {:#x}~{:#x} is outside of the file",
+                self.start, self.end
+            );
+            return false;
+        };
+
+        let width = self.algorithm.width();
+        let encoding = Encoding {
+            endian: self.endian,
+            width: Some(width),
+            ..Encoding::default()
+        };
+        let stored = content
+            .get(self.stored..self.stored + width)
+            .and_then(|bytes| encoding.decode(bytes).ok());
+
+        let computed = self.algorithm.compute(bytes);
+        debug!(
+            "{:?} over {:#x}~{:#x}: computed {computed:#x}, stored {stored:x?}",
+            self.algorithm, self.start, self.end
+        );
+
+        stored == Some(computed as i64)
+    }
+}
@@ -0,0 +1,214 @@
+use anyhow::{Result, anyhow};
+use flate2::read::ZlibDecoder;
+use std::{collections::HashMap, io::Read, ops::Range, path::Path};
+use tracing::debug;
+
+// RetroArch's compressed savestates: a 20-byte header (magic, chunk size,
+// total size) followed by chunks of zlib data, each prefixed by its length
+const RZIP_MAGIC: &[u8] = b"#RZIPv\x01#";
+const RZIP_HEADER_SIZE: usize = 20;
+
+// Newer RetroArch savestates wrap the core's state in blocks after an 8-byte
+// header; each block has a 4-byte tag ("MEM ", "ACHV", "RPLY", "END "), a
+// 4-byte size, and data padded to 8 bytes
+const RASTATE_MAGIC: &[u8] = b"RASTATE";
+const RASTATE_HEADER_SIZE: usize = 8;
+const RASTATE_END_BLOCK: &str = "END";
+
+pub(super) struct Content {
+    pub(super) data: Vec<u8>,
+    sections: Option<HashMap<String, Range<usize>>>,
+    anchors: HashMap<String, Option<usize>>,
+}
+
+// What expressions are evaluated against: the file, plus the offset that
+// addresses are shifted by and the number of the slot being rendered, if any
+#[derive(Clone, Copy)]
+pub(super) struct Scope<'a> {
+    pub(super) content: &'a Content,
+    pub(super) offset: i64,
+    pub(super) slot: Option<usize>,
+}
+
+impl<'a> Scope<'a> {
+    pub(super) fn new(content: &'a Content, offset: i64) -> Self {
+        Self {
+            content,
+            offset,
+            slot: None,
+        }
+    }
+}
+
+// A byte pattern located by searching the file, written as hex bytes with ??
+// wildcards, e.g. "53 41 ?? 45"
+#[derive(Debug)]
+pub(super) struct Signature {
+    bytes: Vec<Option<u8>>,
+}
+
+impl Signature {
+    pub(super) fn parse(signature: &str) -> Result<Self> {
+        let bytes = signature
+            .split_whitespace()
+            .map(|byte| match byte {
+                "??" => Ok(None),
+                byte if byte.len() == 2 => u8::from_str_radix(byte, 16)
+                    .map(Some)
+                    .map_err(|_| anyhow!("Invalid byte {byte:?} in signature")),
+                byte => Err(anyhow!("Invalid byte {byte:?} in signature")),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if bytes.first().is_none_or(|b| b.is_none()) {
+            return Err(anyhow!("Signature must start with a byte"));
+        }
+
+        Ok(Self { bytes })
+    }
+
+    fn find(&self, data: &[u8]) -> Option<usize> {
+        data.windows(self.bytes.len()).position(|window| {
+            window
+                .iter()
+                .zip(&self.bytes)
+                .all(|(b, s)| s.is_none_or(|s| s == *b))
+        })
+    }
+}
+
+impl Content {
+    pub(super) fn new(data: Vec<u8>) -> Self {
+        let sections = if data.starts_with(RASTATE_MAGIC) {
+            match parse_rastate(&data) {
+                Ok(sections) => Some(sections),
+                Err(e) => {
+                    debug!("Could not parse RASTATE container: {e}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        Self {
+            data,
+            sections,
+            anchors: HashMap::new(),
+        }
+    }
+
+    // Each anchor is searched for once, when the file is read
+    pub(super) fn find_anchors(&mut self, anchors: &HashMap<String, Signature>) {
+        self.anchors = anchors
+            .iter()
+            .map(|(name, signature)| {
+                let offset = signature.find(&self.data);
+                debug!("Anchor {name} found at {offset:x?}");
+                (name.clone(), offset)
+            })
+            .collect();
+    }
+
+    pub(super) fn anchor(&self, name: &str) -> Result<usize> {
+        match self.anchors.get(name) {
+            Some(Some(offset)) => Ok(*offset),
+            Some(None) => Err(anyhow!("Anchor {name} not found")),
+            None => Err(anyhow!("Unknown anchor {name}")),
+        }
+    }
+
+    // Files without a container are treated as one big MEM section
+    pub(super) fn section(&self, name: &str) -> Result<Range<usize>> {
+        match &self.sections {
+            Some(sections) => sections
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("No {name} section in savestate")),
+            None if name == "MEM" => Ok(0..self.data.len()),
+            None => Err(anyhow!("No {name} section outside of a RASTATE container")),
+        }
+    }
+}
+
+pub(super) fn read(filename: &Path) -> Result<Content> {
+    let content = std::fs::read(filename)?;
+
+    if content.starts_with(RZIP_MAGIC) {
+        debug!("Decompressing RZIP file {filename:?}");
+        return Ok(Content::new(decompress_rzip(&content)?));
+    }
+
+    Ok(Content::new(content))
+}
+
+fn parse_rastate(data: &[u8]) -> Result<HashMap<String, Range<usize>>> {
+    let mut sections = HashMap::new();
+    let mut offset = RASTATE_HEADER_SIZE;
+
+    loop {
+        let header = data
+            .get(offset..offset + 8)
+            .ok_or_else(|| anyhow!("Truncated RASTATE block at {offset:#x}"))?;
+        let tag = String::from_utf8_lossy(&header[..4]).trim_end().to_string();
+        let size = u32::from_le_bytes(header[4..8].try_into()?) as usize;
+
+        if tag == RASTATE_END_BLOCK {
+            return Ok(sections);
+        }
+
+        let start = offset + 8;
+        if start + size > data.len() {
+            return Err(anyhow!(
+                "RASTATE block {tag} extends past the end of the file"
+            ));
+        }
+
+        debug!("RASTATE block {tag} at {start:#x}, {size} bytes");
+        sections.insert(tag, start..start + size);
+        offset = start + size.next_multiple_of(8);
+    }
+}
+
+fn decompress_rzip(content: &[u8]) -> Result<Vec<u8>> {
+    let header = content
+        .get(..RZIP_HEADER_SIZE)
+        .ok_or_else(|| anyhow!("Truncated RZIP header"))?;
+    let total_size = u64::from_le_bytes(header[12..20].try_into()?) as usize;
+
+    // The header's size can't be trusted for allocating up front, since a
+    // damaged file could claim anything; zlib rarely does better than 64:1
+    let mut output = Vec::with_capacity(total_size.min(content.len().saturating_mul(64)));
+    let mut rest = &content[RZIP_HEADER_SIZE..];
+
+    while output.len() < total_size {
+        let (size, data) = rest
+            .split_first_chunk::<4>()
+            .ok_or_else(|| anyhow!("Truncated RZIP chunk header"))?;
+        let size = u32::from_le_bytes(*size) as usize;
+        let chunk = data
+            .get(..size)
+            .ok_or_else(|| anyhow!("Truncated RZIP chunk"))?;
+
+        // Reading one byte past what's left is enough to tell it overflowed
+        let remaining = (total_size - output.len()) as u64;
+        ZlibDecoder::new(chunk)
+            .take(remaining + 1)
+            .read_to_end(&mut output)?;
+        if output.len() > total_size {
+            return Err(anyhow!(
+                "RZIP decompressed past the {total_size} bytes in its header"
+            ));
+        }
+        rest = &data[size..];
+    }
+
+    if output.len() != total_size {
+        return Err(anyhow!(
+            "RZIP decompressed to {} bytes, expected {total_size}",
+            output.len()
+        ));
+    }
+
+    Ok(output)
+}
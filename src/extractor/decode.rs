@@ -0,0 +1,148 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// Bits are numbered from the least significant bit of the decoded value
+#[derive(Debug, Clone, Copy)]
+pub(super) struct BitRange {
+    low: u32,
+    high: u32,
+}
+
+impl BitRange {
+    pub(super) fn parse(bits: &str) -> Option<Self> {
+        let (low, high) = match bits.split_once('-') {
+            Some((low, high)) => (low.parse().ok()?, high.parse().ok()?),
+            None => {
+                let bit = bits.parse().ok()?;
+                (bit, bit)
+            }
+        };
+
+        (low <= high && high < 64).then_some(Self { low, high })
+    }
+
+    pub(super) fn extract(&self, value: i64) -> i64 {
+        let width = self.high - self.low + 1;
+        let value = (value as u64) >> self.low;
+        if width == 64 {
+            value as i64
+        } else {
+            (value & ((1 << width) - 1)) as i64
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Endian {
+    #[serde(rename = "le")]
+    Little,
+    #[serde(rename = "be")]
+    Big,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Encoding {
+    pub(super) endian: Endian,
+    pub(super) signed: bool,
+    pub(super) bcd: bool,
+    pub(super) width: Option<usize>,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self {
+            endian: Endian::Little,
+            signed: false,
+            bcd: false,
+            width: None,
+        }
+    }
+}
+
+impl Encoding {
+    pub(super) fn is_modifier(name: &str) -> bool {
+        Self::default().apply(name).is_ok()
+    }
+
+    pub(super) fn apply(&mut self, modifier: &str) -> Result<()> {
+        match modifier {
+            "le" => self.endian = Endian::Little,
+            "be" => self.endian = Endian::Big,
+            "bcd" => self.bcd = true,
+            m => {
+                let (signed, bits) = match m.split_at_checked(1) {
+                    Some(("u", bits)) => (false, bits),
+                    Some(("i", bits)) => (true, bits),
+                    _ => return Err(anyhow!("Unknown type {m:?}")),
+                };
+                let width = match bits.parse::<usize>() {
+                    Ok(bits @ (8 | 16 | 24 | 32 | 64)) => bits / 8,
+                    _ => return Err(anyhow!("Unknown type {m:?}")),
+                };
+                self.signed = signed;
+                self.width = Some(width);
+            }
+        }
+
+        if self.bcd && self.signed {
+            return Err(anyhow!("BCD values cannot be signed"));
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn decode(&self, bytes: &[u8]) -> Result<i64> {
+        if bytes.len() > 8 {
+            return Err(anyhow!("Cannot read {} bytes into a number", bytes.len()));
+        }
+
+        let ordered: Box<dyn Iterator<Item = &u8>> = match self.endian {
+            Endian::Little => Box::new(bytes.iter().rev()),
+            Endian::Big => Box::new(bytes.iter()),
+        };
+
+        let mut result: i64 = 0;
+        for byte in ordered {
+            if self.bcd {
+                let (high, low) = (byte >> 4, byte & 0xF);
+                if high > 9 || low > 9 {
+                    return Err(anyhow!("Invalid BCD byte {byte:#04x}"));
+                }
+                result = result * 100 + (high * 10 + low) as i64;
+            } else {
+                result = (result << 8) | *byte as i64;
+            }
+        }
+
+        if self.signed && bytes.len() < 8 {
+            let shift = 64 - bytes.len() * 8;
+            result = (result << shift) >> shift;
+        }
+
+        Ok(result)
+    }
+}
+
+// A game-specific text encoding, like a ROM hacking .tbl file
+#[derive(Debug)]
+pub(super) struct Charset {
+    pub(super) characters: HashMap<u8, String>,
+    pub(super) terminator: Option<u8>,
+}
+
+impl Charset {
+    pub(super) fn decode(&self, bytes: &[u8]) -> String {
+        let mut text = String::new();
+        for byte in bytes {
+            if Some(*byte) == self.terminator {
+                break;
+            }
+            match self.characters.get(byte) {
+                Some(c) => text.push_str(c),
+                None => text.push('?'),
+            }
+        }
+        text.trim_end().to_string()
+    }
+}
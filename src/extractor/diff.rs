@@ -0,0 +1,45 @@
+use super::container;
+use anyhow::Result;
+use std::{ops::RangeInclusive, path::Path};
+
+// A run of consecutive bytes that differ between two saves; when one save is
+// longer, its extra bytes are one last change
+pub struct Change {
+    pub range: RangeInclusive<usize>,
+    pub before: Vec<u8>,
+    pub after: Vec<u8>,
+}
+
+// Compares the saves as they're extracted, so compressed savestates are
+// compared after decompression
+pub fn diff(before: &Path, after: &Path) -> Result<Vec<Change>> {
+    let before = container::read(before)?.data;
+    let after = container::read(after)?.data;
+    let len = before.len().max(after.len());
+
+    let mut changes = Vec::new();
+    let mut start = None;
+    for i in 0..=len {
+        let differs = i < len && before.get(i) != after.get(i);
+        match (differs, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                changes.push(Change {
+                    range: s..=i - 1,
+                    before: before
+                        .get(s..i.min(before.len()))
+                        .unwrap_or_default()
+                        .to_vec(),
+                    after: after
+                        .get(s..i.min(after.len()))
+                        .unwrap_or_default()
+                        .to_vec(),
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(changes)
+}
@@ -0,0 +1,771 @@
+use super::{
+    container::Scope,
+    decode::{BitRange, Encoding},
+    format::Format,
+};
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use std::{fmt, ops::RangeInclusive, sync::OnceLock};
+
+#[derive(Debug, Clone)]
+pub(super) struct ParseError {
+    pub(super) column: usize,
+    pub(super) message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Op {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+}
+
+// How [...] pointers are read: reads inside the brackets default to this
+// encoding, and base is subtracted from the value to get a file offset
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Pointer {
+    pub(super) encoding: Encoding,
+    pub(super) base: i64,
+}
+
+// Where a read's offsets are counted from
+#[derive(Debug, Clone)]
+pub(super) enum Base {
+    Section(String),
+    Anchor(String),
+    Pointer(Box<Expr>, i64),
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Read {
+    base: Option<Base>,
+    start: usize,
+    end: Option<usize>,
+    bits: Option<BitRange>,
+    encoding: Encoding,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Expr {
+    Literal(i64),
+    // The 1-based number of the slot being rendered
+    Slot,
+    Read(Read),
+    Popcount(Read),
+    Negate(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Output {
+    Number,
+    Table(String),
+    Text(String),
+}
+
+// The contents of a {...} placeholder: an expression, an optional table or
+// text modifier and an optional format after the final :
+#[derive(Debug, Clone)]
+pub(super) struct Placeholder {
+    pub(super) source: String,
+    pub(super) column: usize,
+    pub(super) expr: Expr,
+    pub(super) output: Output,
+    pub(super) format: Format,
+}
+
+impl Placeholder {
+    // column is the 1-based column of the placeholder's first character, so
+    // that errors point into the whole pattern
+    pub(super) fn parse(source: &str, column: usize, pointer: Pointer) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            source,
+            pos: 0,
+            column,
+            bare_hex: is_legacy_address(source),
+            pointer,
+            encoding: Encoding::default(),
+        };
+
+        let expr = parser.expr()?;
+
+        let mut output = Output::Number;
+        while parser.eat('|') {
+            let start = parser.pos;
+            match parser.ident() {
+                Some(modifier @ ("table" | "text")) => {
+                    if !matches!(output, Output::Number) {
+                        return Err(parser.error_at(start, "Only one table or text is allowed"));
+                    }
+                    if !parser.eat(':') {
+                        return Err(parser.error(&format!("Expected : after {modifier}")));
+                    }
+                    let Some(name) = parser.ident() else {
+                        return Err(parser.error(&format!("Expected a {modifier} name")));
+                    };
+
+                    output = if modifier == "table" {
+                        Output::Table(name.to_string())
+                    } else if matches!(expr, Expr::Read(_)) {
+                        Output::Text(name.to_string())
+                    } else {
+                        return Err(parser.error_at(start, "text must follow a read"));
+                    };
+                }
+                Some(name) if Encoding::is_modifier(name) => {
+                    return Err(parser.error_at(start, "Type suffix must follow a read"));
+                }
+                Some(name) => {
+                    return Err(parser.error_at(start, &format!("Unknown modifier {name:?}")));
+                }
+                None => return Err(parser.error("Expected a modifier after |")),
+            }
+        }
+
+        let format = if parser.eat(':') {
+            parser.skip_whitespace();
+            let format =
+                Format::parse(parser.rest().trim()).map_err(|e| parser.error(&e.to_string()))?;
+            parser.pos = parser.source.len();
+            format
+        } else {
+            Format::default()
+        };
+
+        if parser.peek().is_some() {
+            return Err(parser.error("Unexpected character"));
+        }
+
+        Ok(Self {
+            source: source.trim().to_string(),
+            column,
+            expr,
+            output,
+            format,
+        })
+    }
+}
+
+impl Expr {
+    // Conditions use the same language, where comparisons, and, or and not
+    // evaluate to 1 or 0 and {...} groups like parentheses
+    pub(super) fn parse_condition(source: &str, pointer: Pointer) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            source,
+            pos: 0,
+            column: 1,
+            bare_hex: false,
+            pointer,
+            encoding: Encoding::default(),
+        };
+
+        let expr = parser.expr()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("Unexpected character"));
+        }
+
+        Ok(expr)
+    }
+}
+
+// Before expressions existed, addresses didn't need a 0x prefix, so a
+// placeholder that is nothing but a bare hex location keeps meaning an address.
+// Each end of a range could have the prefix or not, as in {10~0x12}.
+fn is_legacy_address(source: &str) -> bool {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r"^\s*(?:popcount\s+)?(?:0x)?[0-9a-fA-F]+(?:~(?:0x)?[0-9a-fA-F]+)?(?:\.[0-9]+(?:-[0-9]+)?)?\s*(?:[|:].*)?$")
+            .unwrap()
+    });
+    re.is_match(source)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    column: usize,
+    bare_hex: bool,
+    pointer: Pointer,
+    // The default encoding for reads, which differs inside pointers
+    encoding: Encoding,
+}
+
+impl<'a> Parser<'a> {
+    fn error_at(&self, pos: usize, message: &str) -> ParseError {
+        ParseError {
+            column: self.column + self.source[..pos].chars().count(),
+            message: message.to_string(),
+        }
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        self.error_at(self.pos, message)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    // Consumes characters matching the predicate without skipping whitespace
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn ident(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        if !self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        {
+            return None;
+        }
+        Some(self.take_while(|c| c.is_ascii_alphanumeric() || c == '_'))
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let matches = rest.strip_prefix(word).is_some_and(|after| {
+            !after
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+        if matches {
+            self.pos += word.len();
+        }
+        matches
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.and()?;
+        while self.keyword("or") {
+            let rhs = self.and()?;
+            lhs = Expr::Binary(Op::Or, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.not()?;
+        while self.keyword("and") {
+            let rhs = self.not()?;
+            lhs = Expr::Binary(Op::And, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn not(&mut self) -> Result<Expr, ParseError> {
+        if self.keyword("not") {
+            Ok(Expr::Not(Box::new(self.not()?)))
+        } else {
+            self.comparison()
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ParseError> {
+        let lhs = self.additive()?;
+        let op = if self.eat_str("==") {
+            Op::Equal
+        } else if self.eat_str("!=") {
+            Op::NotEqual
+        } else if self.eat_str("<=") {
+            Op::LessEqual
+        } else if self.eat_str(">=") {
+            Op::GreaterEqual
+        } else if self.eat('<') {
+            Op::Less
+        } else if self.eat('>') {
+            Op::Greater
+        } else {
+            return Ok(lhs);
+        };
+        let rhs = self.additive()?;
+        Ok(Expr::Binary(op, Box::new(lhs), Box::new(rhs)))
+    }
+
+    fn additive(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.term()?;
+        loop {
+            let op = if self.eat('+') {
+                Op::Add
+            } else if self.eat('-') {
+                Op::Subtract
+            } else {
+                return Ok(lhs);
+            };
+            let rhs = self.term()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                Op::Multiply
+            } else if self.eat('/') {
+                Op::Divide
+            } else if self.eat('%') {
+                Op::Modulo
+            } else {
+                return Ok(lhs);
+            };
+            let rhs = self.unary()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if self.eat('-') {
+            Ok(Expr::Negate(Box::new(self.unary()?)))
+        } else {
+            self.postfix()
+        }
+    }
+
+    // Type suffixes bind to the read directly before them
+    fn postfix(&mut self) -> Result<Expr, ParseError> {
+        let start = self.pos;
+        let mut expr = self.primary()?;
+
+        loop {
+            let before = self.pos;
+            if !self.eat('|') {
+                break;
+            }
+
+            let name_start = self.pos;
+            let Some(name) = self.ident() else {
+                return Err(self.error("Expected a type after |"));
+            };
+
+            if !Encoding::is_modifier(name) {
+                self.pos = before;
+                break;
+            }
+
+            let read = match &mut expr {
+                Expr::Read(read) | Expr::Popcount(read) => read,
+                _ => return Err(self.error_at(name_start, "Type suffix must follow a read")),
+            };
+
+            read.encoding
+                .apply(name)
+                .map_err(|e| self.error_at(name_start, &e.to_string()))?;
+        }
+
+        if let Expr::Read(read) | Expr::Popcount(read) = &expr
+            && let (Some(end), Some(width)) = (read.end, read.encoding.width)
+            && end.checked_sub(read.start) != Some(width - 1)
+        {
+            return Err(self.error_at(
+                start,
+                &format!("Range does not match width of {width} bytes"),
+            ));
+        }
+
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        match self.peek() {
+            Some(open @ ('(' | '{')) => {
+                self.pos += 1;
+                let expr = self.expr()?;
+                let close = if open == '(' { ')' } else { '}' };
+                if !self.eat(close) {
+                    return Err(self.error(&format!("Expected {close}")));
+                }
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() => {
+                if self.bare_hex || self.rest().starts_with("0x") {
+                    Ok(Expr::Read(self.read()?))
+                } else {
+                    let start = self.pos;
+                    let digits = self.take_while(|c| c.is_ascii_digit());
+                    digits
+                        .parse()
+                        .map(Expr::Literal)
+                        .map_err(|_| self.error_at(start, "Invalid number"))
+                }
+            }
+            Some(c)
+                if c.is_ascii_alphabetic()
+                    && self.bare_hex
+                    && !self.rest().starts_with("popcount") =>
+            {
+                Ok(Expr::Read(self.read()?))
+            }
+            Some(c) if c.is_ascii_uppercase() => {
+                let section = self.take_while(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+                Ok(Expr::Read(
+                    self.based_read(Base::Section(section.to_string()))?,
+                ))
+            }
+            Some('[') => {
+                self.pos += 1;
+                let outer = std::mem::replace(&mut self.encoding, self.pointer.encoding);
+                let pointer = self.expr();
+                self.encoding = outer;
+                let pointer = pointer?;
+
+                if !self.eat(']') {
+                    return Err(self.error("Expected ]"));
+                }
+
+                let base = Base::Pointer(Box::new(pointer), self.pointer.base);
+                Ok(Expr::Read(self.based_read(base)?))
+            }
+            Some('@') => {
+                self.pos += 1;
+                let Some(anchor) = self.ident() else {
+                    return Err(self.error("Expected an anchor name after @"));
+                };
+                Ok(Expr::Read(
+                    self.based_read(Base::Anchor(anchor.to_string()))?,
+                ))
+            }
+            Some(_) => {
+                let start = self.pos;
+                match self.ident() {
+                    Some("slot") => Ok(Expr::Slot),
+                    Some("popcount") => match self.postfix()? {
+                        Expr::Read(read) => Ok(Expr::Popcount(read)),
+                        _ => Err(self.error_at(start, "popcount must be followed by a read")),
+                    },
+                    Some(name) => Err(self.error_at(start, &format!("Unknown name {name:?}"))),
+                    None => Err(self.error("Expected a value")),
+                }
+            }
+            None => Err(self.error("Expected a value")),
+        }
+    }
+
+    fn address(&mut self) -> Result<usize, ParseError> {
+        let start = self.pos;
+        if self.rest().starts_with("0x") {
+            self.pos += 2;
+        }
+        let digits = self.take_while(|c| c.is_ascii_hexdigit());
+        usize::from_str_radix(digits, 16).map_err(|_| self.error_at(start, "Invalid address"))
+    }
+
+    // A base may be followed by +offset; without one, the read is at the base
+    fn based_read(&mut self, base: Base) -> Result<Read, ParseError> {
+        let mut read = if self.eat('+') {
+            self.read()?
+        } else {
+            Read {
+                base: None,
+                start: 0,
+                end: None,
+                bits: None,
+                encoding: self.encoding,
+            }
+        };
+        read.base = Some(base);
+        Ok(read)
+    }
+
+    // A read is written without spaces: 0x260, 0x260~0x262, 0x1A0.3, 0x1A0.0-4
+    fn read(&mut self) -> Result<Read, ParseError> {
+        self.skip_whitespace();
+        let start = self.address()?;
+
+        let end = if self.rest().starts_with('~') {
+            self.pos += 1;
+            Some(self.address()?)
+        } else {
+            None
+        };
+
+        let bits = if self.rest().starts_with('.') {
+            self.pos += 1;
+            let bits_start = self.pos;
+            let bits = self.take_while(|c| c.is_ascii_digit() || c == '-');
+            match BitRange::parse(bits) {
+                Some(bits) => Some(bits),
+                None => return Err(self.error_at(bits_start, "Invalid bit range")),
+            }
+        } else {
+            None
+        };
+
+        // An explicit range overrides the default width
+        let encoding = match end {
+            Some(_) => Encoding {
+                width: None,
+                ..self.encoding
+            },
+            None => self.encoding,
+        };
+
+        Ok(Read {
+            base: None,
+            start,
+            end,
+            bits,
+            encoding,
+        })
+    }
+}
+
+impl Read {
+    // Offsets are relative to the base, if any, and reads from a section must
+    // stay inside it. Everything but a pointer's target is shifted by the
+    // scope's offset, from a slot or an included config.
+    pub(super) fn bytes<'c>(&self, scope: Scope<'c>) -> Result<&'c [u8]> {
+        Ok(&scope.content.data[self.range(scope)?])
+    }
+
+    pub(super) fn range(&self, scope: Scope) -> Result<RangeInclusive<usize>> {
+        let content = scope.content;
+        let outside = || anyhow!("Range starting at {:#x} is outside of the file", self.start);
+        let end = match (self.end, self.encoding.width) {
+            (Some(end), _) => end,
+            (None, Some(width)) => self.start.checked_add(width - 1).ok_or_else(outside)?,
+            (None, None) => self.start,
+        };
+
+        let (range, offset) = match &self.base {
+            Some(Base::Section(section)) => (content.section(section)?, scope.offset),
+            Some(Base::Anchor(anchor)) => {
+                (content.anchor(anchor)?..content.data.len(), scope.offset)
+            }
+            Some(Base::Pointer(pointer, base)) => {
+                let address = pointer
+                    .eval(scope)?
+                    .checked_sub(*base)
+                    .ok_or_else(|| anyhow!("Overflow"))?;
+                let address = usize::try_from(address)
+                    .map_err(|_| anyhow!("Pointer {address:#x} is before the start of the file"))?;
+                (address..content.data.len(), 0)
+            }
+            None => (0..content.data.len(), scope.offset),
+        };
+
+        let base = (range.start as i64)
+            .checked_add(offset)
+            .and_then(|base| usize::try_from(base).ok())
+            .ok_or_else(|| anyhow!("Offset {offset:#x} is before the start of the file"))?;
+        let start = base.checked_add(self.start).ok_or_else(outside)?;
+        base.checked_add(end)
+            .filter(|end| *end < range.end && *end < content.data.len())
+            .map(|end| start..=end)
+            .filter(|range| range.start() <= range.end())
+            .ok_or_else(|| anyhow!("Range {:#x}~{end:#x} is outside of the file", self.start))
+    }
+
+    fn eval(&self, scope: Scope) -> Result<i64> {
+        let value = self.encoding.decode(self.bytes(scope)?)?;
+        Ok(match self.bits {
+            Some(bits) => bits.extract(value),
+            None => value,
+        })
+    }
+}
+
+impl Expr {
+    // The reads whose bytes make up the value, not counting pointers
+    pub(super) fn reads(&self) -> Vec<&Read> {
+        match self {
+            Expr::Literal(_) | Expr::Slot => Vec::new(),
+            Expr::Read(read) | Expr::Popcount(read) => vec![read],
+            Expr::Negate(expr) | Expr::Not(expr) => expr.reads(),
+            Expr::Binary(_, left, right) => {
+                let mut reads = left.reads();
+                reads.extend(right.reads());
+                reads
+            }
+        }
+    }
+
+    pub(super) fn eval(&self, scope: Scope) -> Result<i64> {
+        match self {
+            Expr::Literal(value) => Ok(*value),
+            Expr::Slot => scope
+                .slot
+                .map(|slot| slot as i64)
+                .ok_or_else(|| anyhow!("slot is only available inside slots")),
+            Expr::Read(read) => read.eval(scope),
+            Expr::Popcount(read) => match read.bits {
+                Some(_) => Ok(read.eval(scope)?.count_ones() as i64),
+                None => Ok(read
+                    .bytes(scope)?
+                    .iter()
+                    .map(|b| b.count_ones() as i64)
+                    .sum()),
+            },
+            Expr::Negate(expr) => expr
+                .eval(scope)?
+                .checked_neg()
+                .ok_or_else(|| anyhow!("Overflow")),
+            Expr::Not(expr) => Ok((expr.eval(scope)? == 0) as i64),
+            Expr::Binary(Op::And, lhs, rhs) => {
+                Ok((lhs.eval(scope)? != 0 && rhs.eval(scope)? != 0) as i64)
+            }
+            Expr::Binary(Op::Or, lhs, rhs) => {
+                Ok((lhs.eval(scope)? != 0 || rhs.eval(scope)? != 0) as i64)
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(scope)?, rhs.eval(scope)?);
+                match op {
+                    Op::Add => lhs.checked_add(rhs),
+                    Op::Subtract => lhs.checked_sub(rhs),
+                    Op::Multiply => lhs.checked_mul(rhs),
+                    Op::Divide => lhs.checked_div(rhs),
+                    Op::Modulo => lhs.checked_rem(rhs),
+                    Op::Equal => Some((lhs == rhs) as i64),
+                    Op::NotEqual => Some((lhs != rhs) as i64),
+                    Op::Less => Some((lhs < rhs) as i64),
+                    Op::LessEqual => Some((lhs <= rhs) as i64),
+                    Op::Greater => Some((lhs > rhs) as i64),
+                    Op::GreaterEqual => Some((lhs >= rhs) as i64),
+                    Op::And | Op::Or => unreachable!(),
+                }
+                .ok_or_else(|| anyhow!("Overflow or division by zero"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::container::Content;
+
+    fn pointer() -> Pointer {
+        Pointer {
+            encoding: Encoding::default(),
+            base: 0,
+        }
+    }
+
+    // Every byte holds its own offset, so a one-byte read at 0x10 is 16
+    fn content() -> Content {
+        Content::new((0..=255).collect())
+    }
+
+    fn eval(source: &str) -> i64 {
+        let content = content();
+        Placeholder::parse(source, 1, pointer())
+            .unwrap()
+            .expr
+            .eval(Scope::new(&content, 0))
+            .unwrap()
+    }
+
+    fn condition(source: &str) -> i64 {
+        let content = content();
+        Expr::parse_condition(source, pointer())
+            .unwrap()
+            .eval(Scope::new(&content, 0))
+            .unwrap()
+    }
+
+    fn error(source: &str, column: usize) -> ParseError {
+        Placeholder::parse(source, column, pointer()).unwrap_err()
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3"), 7);
+        assert_eq!(eval("(1 + 2) * 3"), 9);
+        assert_eq!(eval("10 - 4 - 3"), 3);
+        assert_eq!(eval("-0x10 + 20"), 4);
+        assert_eq!(condition("1 + 1 == 2 and 0 or 1"), 1);
+        assert_eq!(condition("not 0 and 0"), 0);
+        assert_eq!(condition("{0x10} == 16 and {0x11} < 16"), 0);
+    }
+
+    #[test]
+    fn legacy_addresses_are_bare_hex() {
+        assert_eq!(eval("10"), 0x10);
+        assert_eq!(eval("1A"), 0x1A);
+        assert_eq!(eval("10~11"), 0x1110);
+        assert_eq!(eval("10~0x11"), 0x1110);
+        assert_eq!(eval("0x10~11"), 0x1110);
+        assert_eq!(eval("13.0-1"), 0b11);
+        assert_eq!(eval("popcount 10~11"), 3);
+    }
+
+    #[test]
+    fn type_suffixes_bind_to_the_read_before_them() {
+        assert_eq!(eval("0x10|u16 + 1"), 0x1111);
+        assert_eq!(eval("1 + 0x10|u16|be"), 0x1012);
+        assert_eq!(eval("0x10~0x11|be"), 0x1011);
+
+        let e = error("(0x10 + 1)|u16", 1);
+        assert_eq!(e.column, 12);
+        assert_eq!(e.message, "Type suffix must follow a read");
+    }
+
+    #[test]
+    fn errors_point_into_the_pattern() {
+        // As if the placeholder started at column 5 of "HP: {...}"
+        let e = error("0x10 +", 5);
+        assert_eq!(e.column, 11);
+        assert_eq!(e.message, "Expected a value");
+
+        let e = error("0x10~0x13|u16", 5);
+        assert_eq!(e.column, 5);
+        assert_eq!(e.message, "Range does not match width of 2 bytes");
+
+        let e = error("0x10 )", 5);
+        assert_eq!(e.column, 10);
+        assert_eq!(e.message, "Unexpected character");
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::{Result, anyhow};
+
+// How a number is displayed, from the part of a placeholder after the final :
+//
+// - a width, padded with zeros when it starts with 0: "5", "02"
+// - , for thousands separators: ",", "8,"
+// - .N for N decimals, the value being in units of 10^-N unless divided by an
+//   explicit scale: ".2" (cents), ".1/60"
+// - x, X, b or o for hex, binary or octal: "x", "04X"
+// - hms, hm or ms to show seconds as a duration, or frames with @rate:
+//   "hms", "hms@60", "ms@59.94"
+#[derive(Debug, Clone, Default)]
+pub(super) struct Format {
+    width: usize,
+    zero_pad: bool,
+    style: Style,
+}
+
+#[derive(Debug, Clone, Default)]
+enum Style {
+    #[default]
+    Decimal,
+    Thousands,
+    Fixed {
+        decimals: usize,
+        scale: f64,
+        thousands: bool,
+    },
+    Radix(char),
+    Duration {
+        fields: Fields,
+        rate: Option<f64>,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Fields {
+    HoursMinutesSeconds,
+    HoursMinutes,
+    MinutesSeconds,
+}
+
+impl Format {
+    pub(super) fn parse(spec: &str) -> Result<Self> {
+        let (fields, rate) = match spec.split_once('@') {
+            Some((fields, rate)) => {
+                let rate = rate
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|rate| *rate > 0.0)
+                    .ok_or_else(|| anyhow!("Invalid frame rate {rate:?}"))?;
+                (fields, Some(rate))
+            }
+            None => (spec, None),
+        };
+
+        let fields = match fields {
+            "hms" => Some(Fields::HoursMinutesSeconds),
+            "hm" => Some(Fields::HoursMinutes),
+            "ms" => Some(Fields::MinutesSeconds),
+            _ if rate.is_some() => return Err(anyhow!("A frame rate needs hms, hm or ms")),
+            _ => None,
+        };
+        if let Some(fields) = fields {
+            return Ok(Self {
+                style: Style::Duration { fields, rate },
+                ..Self::default()
+            });
+        }
+
+        let digits = spec
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(spec.len());
+        let (width, mut rest) = spec.split_at(digits);
+        let mut format = Self {
+            width: if width.is_empty() { 0 } else { width.parse()? },
+            zero_pad: width.starts_with('0'),
+            style: Style::Decimal,
+        };
+
+        let thousands = rest.starts_with(',');
+        if thousands {
+            rest = &rest[1..];
+            format.style = Style::Thousands;
+        }
+
+        if let Some(fixed) = rest.strip_prefix('.') {
+            let (decimals, scale) = match fixed.split_once('/') {
+                Some((decimals, scale)) => (decimals, Some(scale)),
+                None => (fixed, None),
+            };
+            let decimals = decimals
+                .parse::<usize>()
+                .map_err(|_| anyhow!("Invalid number of decimals {decimals:?}"))?;
+            let scale = match scale {
+                Some(scale) => scale
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|scale| *scale != 0.0)
+                    .ok_or_else(|| anyhow!("Invalid scale {scale:?}"))?,
+                None => 10f64.powi(decimals as i32),
+            };
+            format.style = Style::Fixed {
+                decimals,
+                scale,
+                thousands,
+            };
+            return Ok(format);
+        }
+
+        match rest {
+            "" => Ok(format),
+            radix @ ("x" | "X" | "b" | "o") if !thousands => {
+                format.style = Style::Radix(radix.chars().next().unwrap());
+                Ok(format)
+            }
+            _ => Err(anyhow!("Invalid format {spec:?}")),
+        }
+    }
+
+    pub(super) fn apply(&self, value: i64) -> String {
+        let text = match &self.style {
+            Style::Decimal => value.to_string(),
+            Style::Thousands => group_thousands(&value.to_string()),
+            Style::Fixed {
+                decimals,
+                scale,
+                thousands,
+            } => {
+                let text = format!("{:.*}", decimals, value as f64 / scale);
+                if *thousands {
+                    group_thousands(&text)
+                } else {
+                    text
+                }
+            }
+            Style::Radix('x') => format!("{value:x}"),
+            Style::Radix('X') => format!("{value:X}"),
+            Style::Radix('b') => format!("{value:b}"),
+            Style::Radix(_) => format!("{value:o}"),
+            Style::Duration { fields, rate } => {
+                let seconds = match rate {
+                    Some(rate) => (value.unsigned_abs() as f64 / rate) as u64,
+                    None => value.unsigned_abs(),
+                };
+                let (h, m, s) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+                let text = match fields {
+                    Fields::HoursMinutesSeconds => format!("{h}:{m:02}:{s:02}"),
+                    Fields::HoursMinutes => format!("{h}:{m:02}"),
+                    Fields::MinutesSeconds => format!("{}:{s:02}", seconds / 60),
+                };
+                if value < 0 { format!("-{text}") } else { text }
+            }
+        };
+
+        self.pad(text)
+    }
+
+    // Zeros go after the sign, so -5 padded to 03 is -05
+    fn pad(&self, text: String) -> String {
+        let len = text.chars().count();
+        if len >= self.width {
+            return text;
+        }
+
+        let padding = self.width - len;
+        if !self.zero_pad {
+            return format!("{}{text}", " ".repeat(padding));
+        }
+
+        match text.strip_prefix('-') {
+            Some(digits) => format!("-{}{digits}", "0".repeat(padding)),
+            None => format!("{}{text}", "0".repeat(padding)),
+        }
+    }
+}
+
+// Separates the digits before any decimal point into groups of three
+fn group_thousands(text: &str) -> String {
+    let (sign, text) = match text.strip_prefix('-') {
+        Some(text) => ("-", text),
+        None => ("", text),
+    };
+    let (integer, fraction) = match text.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (text, None),
+    };
+
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    match fraction {
+        Some(fraction) => format!("{sign}{grouped}.{fraction}"),
+        None => format!("{sign}{grouped}"),
+    }
+}
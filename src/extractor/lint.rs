@@ -0,0 +1,233 @@
+use super::{
+    Extractor,
+    container::{self, Scope},
+    expr::{Output, Placeholder},
+    matching,
+    template::{Condition, Line, Segment, Template},
+};
+use anyhow::{Result, anyhow};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+// A pattern that would render as ?? or a condition that can't be evaluated,
+// located by line and column in the config it came from when possible
+pub struct Problem {
+    pub file: PathBuf,
+    pub location: Option<(usize, usize)>,
+    pub pattern: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(
+                f,
+                "{}:{line}:{column}: {}",
+                self.file.display(),
+                self.message
+            ),
+            None => write!(
+                f,
+                "{}: {} in {:?}",
+                self.file.display(),
+                self.message,
+                self.pattern
+            ),
+        }
+    }
+}
+
+// A problem within a pattern, where column is 1-based within the pattern
+#[derive(PartialEq)]
+struct Issue {
+    pattern: String,
+    column: usize,
+    message: String,
+}
+
+impl Extractor {
+    // Every pattern is checked for syntax errors and unknown tables and
+    // charsets. Given a save, the lines shown for it are also evaluated
+    // against it, so that reads outside of the file are found too.
+    pub fn lint(&self, save: Option<&Path>) -> Result<Vec<Problem>> {
+        let content = match save {
+            Some(save) => {
+                let mut content = container::read(save)?;
+                content.find_anchors(&self.anchors);
+                Some(content)
+            }
+            None => None,
+        };
+        let matched = save.and_then(|save| matching::lookup(&self.templates, save));
+
+        let mut keys = self.templates.iter().collect::<Vec<_>>();
+        keys.sort_by_key(|(key, _)| *key);
+
+        let mut sources = HashMap::new();
+        let mut problems = Vec::new();
+        for (_, templates) in keys {
+            let scope = match (&content, matched) {
+                (Some(content), Some(matched)) if std::ptr::eq(matched, templates) => {
+                    Some(Scope::new(content, templates.delta))
+                }
+                _ => None,
+            };
+
+            let mut issues = Vec::new();
+            for line in &templates.value {
+                self.lint_line(line, scope, &mut issues);
+            }
+
+            if issues.is_empty() {
+                continue;
+            }
+
+            let source = match sources.get(&templates.source) {
+                Some(source) => source,
+                None => {
+                    let text = std::fs::read_to_string(&templates.source).map_err(|e| {
+                        anyhow!("Could not read config {:?}: {e}", templates.source)
+                    })?;
+                    sources.entry(templates.source.clone()).or_insert(text)
+                }
+            };
+
+            problems.extend(issues.into_iter().map(|issue| Problem {
+                file: templates.source.clone(),
+                location: locate(source, &issue.pattern, issue.column),
+                pattern: issue.pattern,
+                message: issue.message,
+            }));
+        }
+
+        Ok(problems)
+    }
+
+    // Without a scope, only the config itself is checked; lines are evaluated
+    // only where they'd be shown
+    fn lint_line(&self, line: &Line, scope: Option<Scope>, issues: &mut Vec<Issue>) {
+        match line {
+            Line::Template(template) => self.lint_template(template, scope, issues),
+            Line::Conditional {
+                condition, lines, ..
+            } => {
+                let scope = lint_condition(condition, scope, issues);
+                for line in lines {
+                    self.lint_line(line, scope, issues);
+                }
+            }
+            Line::Slots {
+                base,
+                stride,
+                count,
+                in_use,
+                label,
+                lines,
+            } => {
+                if *count == 0 {
+                    lint_condition(in_use, None, issues);
+                    self.lint_template(label, None, issues);
+                    for line in lines {
+                        self.lint_line(line, None, issues);
+                    }
+                }
+
+                for i in 0..*count {
+                    let scope = scope.map(|scope| Scope {
+                        offset: scope.offset + (base + i * stride) as i64,
+                        slot: Some(i + 1),
+                        ..scope
+                    });
+
+                    let scope = lint_condition(in_use, scope, issues);
+                    self.lint_template(label, scope, issues);
+                    for line in lines {
+                        self.lint_line(line, scope, issues);
+                    }
+                }
+            }
+        }
+    }
+
+    fn lint_template(&self, template: &Template, scope: Option<Scope>, issues: &mut Vec<Issue>) {
+        for segment in &template.segments {
+            let (column, message) = match segment {
+                Segment::Text(_) => continue,
+                Segment::Invalid(e) => (e.column, e.message.clone()),
+                Segment::Placeholder(placeholder) => {
+                    let result = match scope {
+                        Some(scope) => self.extract_placeholder(scope, placeholder).map(|_| ()),
+                        None => self.check_output(placeholder),
+                    };
+                    match result {
+                        Ok(()) => continue,
+                        Err(e) => (placeholder.column, e.to_string()),
+                    }
+                }
+            };
+
+            add_issue(issues, &template.pattern, column, message);
+        }
+    }
+
+    fn check_output(&self, placeholder: &Placeholder) -> Result<()> {
+        match &placeholder.output {
+            Output::Table(name) if !self.tables.contains_key(name) => {
+                Err(anyhow!("Unknown table {name:?}"))
+            }
+            Output::Text(name) if !self.charsets.contains_key(name) => {
+                Err(anyhow!("Unknown charset {name:?}"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Returns the scope for the lines under the condition, if they'd be shown
+fn lint_condition<'a>(
+    condition: &Condition,
+    scope: Option<Scope<'a>>,
+    issues: &mut Vec<Issue>,
+) -> Option<Scope<'a>> {
+    match condition {
+        Condition::Always => scope,
+        Condition::Invalid(source, e) => {
+            add_issue(issues, source, e.column, e.message.clone());
+            None
+        }
+        Condition::When(source, expr) => match expr.eval(scope?) {
+            Ok(value) => scope.filter(|_| value != 0),
+            Err(e) => {
+                add_issue(issues, source, 1, e.to_string());
+                None
+            }
+        },
+    }
+}
+
+// Slots can find the same problem once per slot
+fn add_issue(issues: &mut Vec<Issue>, pattern: &str, column: usize, message: String) {
+    let issue = Issue {
+        pattern: pattern.to_string(),
+        column,
+        message,
+    };
+    if !issues.contains(&issue) {
+        issues.push(issue);
+    }
+}
+
+// Finds the pattern as a JSON string in the config; columns are off by one for
+// each escape before the problem
+fn locate(source: &str, pattern: &str, column: usize) -> Option<(usize, usize)> {
+    let quoted = serde_json::to_string(pattern).ok()?;
+    let start = source.find(&quoted)?;
+    let before = &source[..start];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Some((line, before[line_start..].chars().count() + 1 + column))
+}
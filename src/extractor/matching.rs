@@ -0,0 +1,64 @@
+use std::{collections::HashMap, path::Path};
+
+// Config keys are matched against a file in order of precedence:
+//
+// 1. an exact extension, longest first, so "state.auto" beats "auto"
+// 2. a glob with * and ? against an extension or the whole file name, e.g.
+//    "state*" or "*.srm"; when several match, the one with the most literal
+//    characters wins, then the alphabetically first
+pub(super) fn lookup<'a, T>(map: &'a HashMap<String, T>, filename: &Path) -> Option<&'a T> {
+    let name = filename.file_name()?.to_str()?;
+    let extensions = name
+        .match_indices('.')
+        .map(|(i, _)| &name[i + 1..])
+        .collect::<Vec<_>>();
+
+    if let Some(value) = extensions.iter().find_map(|e| map.get(*e)) {
+        return Some(value);
+    }
+
+    map.iter()
+        .filter(|(key, _)| is_glob(key))
+        .filter(|(key, _)| glob_match(key, name) || extensions.iter().any(|e| glob_match(key, e)))
+        .max_by(|(a, _), (b, _)| literal_len(a).cmp(&literal_len(b)).then(b.cmp(a)))
+        .map(|(_, value)| value)
+}
+
+fn is_glob(key: &str) -> bool {
+    key.contains(['*', '?'])
+}
+
+fn literal_len(key: &str) -> usize {
+    key.chars().filter(|c| !matches!(c, '*' | '?')).count()
+}
+
+fn glob_match(glob: &str, text: &str) -> bool {
+    let glob = glob.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+
+    // Backtrack to just after the most recent * when a match fails
+    let (mut g, mut t) = (0, 0);
+    let mut star = None;
+    while t < text.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, t));
+                g += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                g += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_g, star_t)) => {
+                    g = star_g + 1;
+                    t = star_t + 1;
+                    star = Some((star_g, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    glob[g..].iter().all(|c| *c == '*')
+}
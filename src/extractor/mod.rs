@@ -0,0 +1,638 @@
+use crate::internal::full_extension;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, error};
+
+mod checksum;
+mod container;
+mod decode;
+mod diff;
+mod expr;
+mod format;
+mod lint;
+mod matching;
+mod script;
+mod search;
+mod template;
+
+pub use diff::{Change, diff};
+pub use lint::Problem;
+pub use search::{Candidate, find_offsets};
+
+use checksum::{Checksum, ChecksumConfig};
+use container::{Scope, Signature};
+use decode::{Charset, Encoding, Endian};
+use expr::{Expr, Output, Placeholder, Pointer};
+use script::Script;
+use template::{Line, LineConfig, Segment, Template};
+
+pub struct Extractor {
+    templates: HashMap<String, Shifted<Vec<Line>>>,
+    tables: HashMap<String, HashMap<i64, String>>,
+    charsets: HashMap<String, Charset>,
+    checksums: HashMap<String, Shifted<Vec<Checksum>>>,
+    anchors: HashMap<String, Signature>,
+    script: Option<Script>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Validity {
+    Unchecked,
+    Valid,
+    Corrupt,
+}
+
+// Lines are grouped so that each slot of a multi-slot save has its own title
+#[derive(Default, Serialize)]
+pub struct Group {
+    pub title: Option<String>,
+    pub lines: Vec<Entry>,
+}
+
+// A rendered line along with the placeholders that went into it
+#[derive(Serialize)]
+pub struct Entry {
+    pub text: String,
+    pub fields: Vec<Field>,
+}
+
+// A placeholder's source, its value before formatting (absent for text or
+// when it couldn't be read), and the bytes it was read from
+#[derive(Serialize)]
+pub struct Field {
+    pub name: String,
+    pub raw: Option<i64>,
+    pub formatted: String,
+    pub bytes: Vec<RangeInclusive<usize>>,
+}
+
+impl From<String> for Entry {
+    fn from(text: String) -> Self {
+        Self {
+            text,
+            fields: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Extraction {
+    pub groups: Vec<Group>,
+    pub validity: Validity,
+}
+
+// A config entry along with the file it came from and how far its addresses
+// are shifted, which is nonzero for entries inherited from a config included
+// with an offset
+#[derive(Debug)]
+struct Shifted<T> {
+    delta: i64,
+    source: PathBuf,
+    value: T,
+}
+
+#[derive(Deserialize, Debug)]
+struct Config {
+    extends: Option<Extends>,
+
+    #[serde(default)]
+    tables: HashMap<String, HashMap<String, String>>,
+
+    #[serde(default)]
+    charsets: HashMap<String, CharsetConfig>,
+
+    #[serde(default)]
+    checksums: HashMap<String, Vec<ChecksumConfig>>,
+
+    #[serde(default)]
+    anchors: HashMap<String, String>,
+
+    pointer: Option<PointerConfig>,
+
+    #[serde(flatten)]
+    types: HashMap<String, Vec<LineConfig>>,
+}
+
+// Other configs to inherit from, each a path relative to the including file
+// or an object with a path and an offset to shift the included addresses by
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Extends {
+    One(Include),
+    Many(Vec<Include>),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Include {
+    Path(String),
+    Shifted { path: String, offset: String },
+}
+
+// A config merged with everything it includes, where each key overrides the
+// same key from the included configs, and later includes override earlier
+#[derive(Default)]
+struct Resolved {
+    tables: HashMap<String, HashMap<String, String>>,
+    charsets: HashMap<String, CharsetConfig>,
+    checksums: HashMap<String, Shifted<Vec<ChecksumConfig>>>,
+    anchors: HashMap<String, String>,
+    pointer: Option<PointerConfig>,
+    types: HashMap<String, Shifted<Vec<LineConfig>>>,
+}
+
+impl Resolved {
+    fn load(filename: &Path, delta: i64, chain: &mut Vec<PathBuf>) -> Result<Self> {
+        let canonical = filename
+            .canonicalize()
+            .map_err(|e| anyhow!("Could not read config {filename:?}: {e}"))?;
+        if chain.contains(&canonical) {
+            let cycle = chain
+                .iter()
+                .skip_while(|path| **path != canonical)
+                .chain([&canonical])
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>();
+            return Err(anyhow!("Config includes itself: {}", cycle.join(" -> ")));
+        }
+
+        let file = std::fs::File::open(filename)?;
+        let reader = std::io::BufReader::new(file);
+        let config: Config = serde_json::from_reader(reader)
+            .map_err(|e| anyhow!("Could not parse config {filename:?}: {e}"))?;
+
+        chain.push(canonical);
+        let mut resolved = Self::default();
+        let includes = match config.extends {
+            Some(Extends::One(include)) => vec![include],
+            Some(Extends::Many(includes)) => includes,
+            None => Vec::new(),
+        };
+        for include in includes {
+            let (path, offset) = match &include {
+                Include::Path(path) => (path, 0),
+                Include::Shifted { path, offset } => (
+                    path,
+                    parse_signed(offset)
+                        .ok_or_else(|| anyhow!("Invalid include offset {offset:?}"))?,
+                ),
+            };
+            let path = filename.parent().unwrap_or(Path::new(".")).join(path);
+            debug!("Including {path:?} with offset {offset:#x}");
+            resolved.merge(Self::load(&path, delta + offset, chain)?);
+        }
+        chain.pop();
+
+        resolved.merge(Self {
+            tables: config.tables,
+            charsets: config.charsets,
+            checksums: config
+                .checksums
+                .into_iter()
+                .map(|(key, value)| {
+                    let source = filename.to_path_buf();
+                    (
+                        key,
+                        Shifted {
+                            delta,
+                            source,
+                            value,
+                        },
+                    )
+                })
+                .collect(),
+            anchors: config.anchors,
+            pointer: config.pointer,
+            types: config
+                .types
+                .into_iter()
+                .map(|(key, value)| {
+                    let source = filename.to_path_buf();
+                    (
+                        key,
+                        Shifted {
+                            delta,
+                            source,
+                            value,
+                        },
+                    )
+                })
+                .collect(),
+        });
+
+        Ok(resolved)
+    }
+
+    fn merge(&mut self, other: Self) {
+        self.tables.extend(other.tables);
+        self.charsets.extend(other.charsets);
+        self.checksums.extend(other.checksums);
+        self.anchors.extend(other.anchors);
+        self.pointer = other.pointer.or(self.pointer.take());
+        self.types.extend(other.types);
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct CharsetConfig {
+    terminator: Option<String>,
+    characters: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+struct PointerConfig {
+    width: usize,
+    endian: Endian,
+    base: String,
+}
+
+impl Default for PointerConfig {
+    fn default() -> Self {
+        Self {
+            width: 2,
+            endian: Endian::Little,
+            base: "0".to_string(),
+        }
+    }
+}
+
+// Keys in the config are decimal or 0x-prefixed hex
+fn parse_number(s: &str) -> Option<i64> {
+    match s.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+// Include offsets may also be negative, for builds shifted the other way
+fn parse_signed(s: &str) -> Option<i64> {
+    match s.strip_prefix('-') {
+        Some(s) => parse_number(s).map(|n| -n),
+        None => parse_number(s),
+    }
+}
+
+fn parse_byte(s: &str) -> Option<u8> {
+    parse_number(s).and_then(|n| u8::try_from(n).ok())
+}
+
+impl Extractor {
+    // A .rhai script next to the config is run after the config's lines, and
+    // can be used without a config at all
+    pub fn new(filename: &Path) -> Result<Self> {
+        let script_filename = filename.with_extension("rhai");
+        let script = if script_filename.exists() {
+            debug!("Loading script {script_filename:?}");
+            Some(Script::load(&script_filename)?)
+        } else {
+            None
+        };
+
+        let config = if script.is_some() && !filename.exists() {
+            Resolved::default()
+        } else {
+            Resolved::load(filename, 0, &mut Vec::new())?
+        };
+
+        let tables = config
+            .tables
+            .iter()
+            .map(|(name, entries)| {
+                let entries = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = parse_number(key)
+                            .ok_or_else(|| anyhow!("Invalid key {key:?} in table {name:?}"))?;
+                        Ok((key, value.clone()))
+                    })
+                    .collect::<Result<HashMap<_, _>>>()?;
+                Ok((name.clone(), entries))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let charsets = config
+            .charsets
+            .iter()
+            .map(|(name, charset)| {
+                let terminator =
+                    match &charset.terminator {
+                        Some(t) => Some(parse_byte(t).ok_or_else(|| {
+                            anyhow!("Invalid terminator {t:?} in charset {name:?}")
+                        })?),
+                        None => None,
+                    };
+                let characters = charset
+                    .characters
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = parse_byte(key)
+                            .ok_or_else(|| anyhow!("Invalid byte {key:?} in charset {name:?}"))?;
+                        Ok((key, value.clone()))
+                    })
+                    .collect::<Result<HashMap<_, _>>>()?;
+                Ok((
+                    name.clone(),
+                    Charset {
+                        characters,
+                        terminator,
+                    },
+                ))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let checksums = config
+            .checksums
+            .iter()
+            .map(|(extension, checksums)| {
+                let delta = checksums.delta;
+                let value = checksums
+                    .value
+                    .iter()
+                    .map(|c| Checksum::new(c, delta))
+                    .collect::<Result<Vec<_>>>()?;
+                let source = checksums.source.clone();
+                Ok((
+                    extension.clone(),
+                    Shifted {
+                        delta,
+                        source,
+                        value,
+                    },
+                ))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let anchors = config
+            .anchors
+            .iter()
+            .map(|(name, signature)| {
+                let signature = Signature::parse(signature)
+                    .map_err(|e| anyhow!("Invalid anchor {name:?}: {e}"))?;
+                Ok((name.clone(), signature))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let pointer_config = config.pointer.unwrap_or_default();
+        if !matches!(pointer_config.width, 1..=8) {
+            return Err(anyhow!("Invalid pointer width {}", pointer_config.width));
+        }
+        let pointer = Pointer {
+            encoding: Encoding {
+                endian: pointer_config.endian,
+                width: Some(pointer_config.width),
+                ..Encoding::default()
+            },
+            base: parse_number(&pointer_config.base)
+                .ok_or_else(|| anyhow!("Invalid pointer base {:?}", pointer_config.base))?,
+        };
+
+        let templates = config
+            .types
+            .iter()
+            .map(|(extension, lines)| {
+                let value = lines
+                    .value
+                    .iter()
+                    .map(|l| Line::parse(l, pointer))
+                    .collect::<Result<_>>()?;
+                Ok((
+                    extension.clone(),
+                    Shifted {
+                        delta: lines.delta,
+                        source: lines.source.clone(),
+                        value,
+                    },
+                ))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        Ok(Self {
+            templates,
+            tables,
+            charsets,
+            checksums,
+            anchors,
+            script,
+        })
+    }
+
+    // Whether there's a config or script for the game, so that it's worth
+    // loading an extractor
+    pub fn exists(filename: &Path) -> bool {
+        filename.exists() || filename.with_extension("rhai").exists()
+    }
+
+    // Only verifies checksums, without rendering any lines or running the
+    // script, for checking many saves at once
+    pub fn validity(&self, filename: &Path) -> Result<Validity> {
+        let Some(checksums) = matching::lookup(&self.checksums, filename) else {
+            return Ok(Validity::Unchecked);
+        };
+
+        let content = container::read(filename)?;
+        Ok(verify(Some(checksums), &content.data, filename))
+    }
+
+    pub fn extract(&self, filename: &Path) -> Result<Extraction> {
+        let checksums = matching::lookup(&self.checksums, filename);
+        let templates = matching::lookup(&self.templates, filename);
+
+        if checksums.is_none() && templates.is_none() && self.script.is_none() {
+            return Ok(Extraction {
+                groups: Vec::new(),
+                validity: Validity::Unchecked,
+            });
+        }
+
+        let mut content = container::read(filename)?;
+        content.find_anchors(&self.anchors);
+
+        let validity = verify(checksums, &content.data, filename);
+
+        let mut groups = vec![Group::default()];
+        if let Some(templates) = templates {
+            let scope = Scope::new(&content, templates.delta);
+            for line in &templates.value {
+                self.render_line(scope, line, false, &mut groups);
+            }
+        }
+        groups.retain(|g| g.title.is_some() || !g.lines.is_empty());
+
+        if let Some(script) = &self.script {
+            let extension = full_extension(filename).unwrap_or_default();
+            // A broken script shouldn't hide what the templates extracted
+            match script.run(&content, extension) {
+                Ok(script_groups) => groups.extend(script_groups),
+                Err(e) => {
+                    error!("Script error for {filename:?}: {e}");
+                    groups.push(Group {
+                        title: None,
+                        lines: vec![format!("Script error: {e}").into()],
+                    });
+                }
+            }
+        }
+
+        Ok(Extraction { groups, validity })
+    }
+
+    fn render_line(&self, scope: Scope, line: &Line, hide_zero: bool, groups: &mut Vec<Group>) {
+        match line {
+            Line::Template(template) => {
+                if !(hide_zero && self.is_zero(scope, template)) {
+                    let entry = self.render(scope, template);
+                    groups.last_mut().unwrap().lines.push(entry);
+                }
+            }
+            Line::Conditional {
+                condition,
+                hide_zero: hide,
+                lines,
+            } => {
+                if condition.holds(scope) {
+                    for line in lines {
+                        self.render_line(scope, line, hide_zero || *hide, groups);
+                    }
+                }
+            }
+            Line::Slots {
+                base,
+                stride,
+                count,
+                in_use,
+                label,
+                lines,
+            } => {
+                for i in 0..*count {
+                    let scope = Scope {
+                        offset: scope.offset + (base + i * stride) as i64,
+                        slot: Some(i + 1),
+                        ..scope
+                    };
+
+                    if !in_use.holds(scope) {
+                        continue;
+                    }
+
+                    groups.push(Group {
+                        title: Some(self.render(scope, label).text),
+                        lines: Vec::new(),
+                    });
+                    for line in lines {
+                        self.render_line(scope, line, hide_zero, groups);
+                    }
+                }
+
+                // Lines after the slots aren't part of the last one
+                groups.push(Group::default());
+            }
+        }
+    }
+
+    // A line is zero when it has numbers and they're all zero
+    fn is_zero(&self, scope: Scope, template: &Template) -> bool {
+        let mut numbers = template
+            .segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Placeholder(placeholder)
+                    if !matches!(placeholder.output, Output::Text(_)) =>
+                {
+                    Some(placeholder)
+                }
+                _ => None,
+            })
+            .peekable();
+
+        numbers.peek().is_some()
+            && numbers.all(|placeholder| placeholder.expr.eval(scope).is_ok_and(|v| v == 0))
+    }
+
+    fn render(&self, scope: Scope, template: &Template) -> Entry {
+        let mut text = String::new();
+        let mut fields = Vec::new();
+        for segment in &template.segments {
+            match segment {
+                Segment::Text(t) => text.push_str(t),
+                Segment::Placeholder(placeholder) => {
+                    let field = self.extract_field(scope, placeholder);
+                    text.push_str(&field.formatted);
+                    fields.push(field);
+                }
+                Segment::Invalid(_) => text.push_str("??"),
+            }
+        }
+        Entry { text, fields }
+    }
+
+    fn extract_field(&self, scope: Scope, placeholder: &Placeholder) -> Field {
+        let (raw, formatted) = match self.extract_placeholder(scope, placeholder) {
+            Ok(result) => result,
+            Err(e) => {
+                debug!("Could not extract {:?}: {e}", placeholder.source);
+                (None, "??".to_string())
+            }
+        };
+
+        Field {
+            name: placeholder.source.clone(),
+            raw,
+            formatted,
+            bytes: placeholder
+                .expr
+                .reads()
+                .iter()
+                .filter_map(|read| read.range(scope).ok())
+                .collect(),
+        }
+    }
+
+    // The raw value, if numeric, and how it's displayed
+    fn extract_placeholder(
+        &self,
+        scope: Scope,
+        placeholder: &Placeholder,
+    ) -> Result<(Option<i64>, String)> {
+        if let (Output::Text(name), Expr::Read(read)) = (&placeholder.output, &placeholder.expr) {
+            let charset = self
+                .charsets
+                .get(name)
+                .ok_or_else(|| anyhow!("Unknown charset {name:?}"))?;
+            return Ok((None, charset.decode(read.bytes(scope)?)));
+        }
+
+        let result = placeholder.expr.eval(scope)?;
+
+        if let Output::Table(name) = &placeholder.output {
+            let table = self
+                .tables
+                .get(name)
+                .ok_or_else(|| anyhow!("Unknown table {name:?}"))?;
+
+            if let Some(value) = table.get(&result) {
+                debug!("{result} -> {value}");
+                return Ok((Some(result), value.clone()));
+            }
+        }
+
+        Ok((Some(result), placeholder.format.apply(result)))
+    }
+}
+
+fn verify(checksums: Option<&Shifted<Vec<Checksum>>>, data: &[u8], filename: &Path) -> Validity {
+    match checksums {
+        Some(checksums) if checksums.value.iter().all(|c| c.verify(data)) => Validity::Valid,
+        Some(_) => {
+            error!("Checksum mismatch in {filename:?}");
+            Validity::Corrupt
+        }
+        None => Validity::Unchecked,
+    }
+}
@@ -0,0 +1,96 @@
+use super::{Entry, Group, container::Content};
+use anyhow::{Result, anyhow};
+use rhai::{AST, Array, Dynamic, Engine, Map, module_resolvers::DummyModuleResolver};
+use std::path::Path;
+use tracing::debug;
+
+// Enough for a script to walk every byte of a large save a few times, but a
+// runaway loop is stopped long before the UI notices
+const MAX_OPERATIONS: u64 = 5_000_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_STRING_SIZE: usize = 64 * 1024;
+const MAX_COLLECTION_SIZE: usize = 64 * 1024;
+
+// A Rhai script for formats the templates can't describe. It's run with the
+// save's bytes in `data` (after decompression) and its full extension in
+// `extension`, and returns an array of lines, where each item is either a
+// string or a group like #{ title: "Slot 1", lines: ["..."] }
+pub(super) struct Script {
+    engine: Engine,
+    ast: AST,
+}
+
+impl Script {
+    pub(super) fn load(filename: &Path) -> Result<Self> {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(MAX_CALL_LEVELS)
+            .set_max_string_size(MAX_STRING_SIZE)
+            .set_max_array_size(MAX_COLLECTION_SIZE)
+            .set_max_map_size(MAX_COLLECTION_SIZE)
+            .set_module_resolver(DummyModuleResolver::new())
+            .on_print(|s| debug!("Script: {s}"))
+            .on_debug(|s, _, position| debug!("Script at {position}: {s}"));
+        engine.disable_symbol("eval");
+
+        let ast = engine
+            .compile_file(filename.to_path_buf())
+            .map_err(|e| anyhow!("Could not compile script {filename:?}: {e}"))?;
+
+        Ok(Self { engine, ast })
+    }
+
+    pub(super) fn run(&self, content: &Content, extension: &str) -> Result<Vec<Group>> {
+        let mut scope = rhai::Scope::new();
+        scope.push_constant("data", Dynamic::from_blob(content.data.clone()));
+        scope.push_constant("extension", extension.to_string());
+
+        let result = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast)
+            .map_err(|e| anyhow!("Script failed: {e}"))?;
+
+        if result.is_unit() {
+            return Ok(Vec::new());
+        }
+
+        let items = result
+            .try_cast::<Array>()
+            .ok_or_else(|| anyhow!("Script must return an array of lines"))?;
+
+        let mut groups = vec![Group::default()];
+        for item in items {
+            if item.is_string() {
+                groups
+                    .last_mut()
+                    .unwrap()
+                    .lines
+                    .push(Entry::from(item.to_string()));
+                continue;
+            }
+
+            let Some(mut group) = item.try_cast::<Map>() else {
+                return Err(anyhow!(
+                    "Script returned a line that isn't a string or group"
+                ));
+            };
+            let title = group.remove("title").map(|title| title.to_string());
+            let lines = match group.remove("lines") {
+                Some(lines) => lines
+                    .try_cast::<Array>()
+                    .ok_or_else(|| anyhow!("Group lines must be an array"))?
+                    .into_iter()
+                    .map(|line| Entry::from(line.to_string()))
+                    .collect(),
+                None => Vec::new(),
+            };
+
+            groups.push(Group { title, lines });
+            groups.push(Group::default());
+        }
+
+        groups.retain(|g| g.title.is_some() || !g.lines.is_empty());
+        Ok(groups)
+    }
+}
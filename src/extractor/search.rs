@@ -0,0 +1,127 @@
+use super::{container, decode::Encoding};
+use anyhow::{Result, anyhow};
+use std::{fmt, path::Path};
+
+// The ways a number is commonly stored, as the suffixes that read it
+const TYPES: &[&str] = &[
+    "u8", "u16", "be|u16", "u24", "be|u24", "u32", "be|u32", "i8", "i16", "be|i16", "i24",
+    "be|i24", "i32", "be|i32",
+];
+const MAX_BCD_WIDTH: usize = 4;
+
+// A location and encoding that decodes to the known value in every save,
+// written as a placeholder that can be pasted into a config
+pub struct Candidate {
+    pub offset: usize,
+    pub placeholder: String,
+}
+
+impl fmt::Display for Candidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{}}}", self.placeholder)
+    }
+}
+
+struct Layout {
+    encoding: Encoding,
+    width: usize,
+    suffix: String,
+    // BCD has no width suffix, so its reads are written as a range
+    range: bool,
+}
+
+impl Layout {
+    fn placeholder(&self, offset: usize) -> String {
+        if self.range && self.width > 1 {
+            format!(
+                "0x{offset:X}~0x{:X}|{}",
+                offset + self.width - 1,
+                self.suffix
+            )
+        } else {
+            format!("0x{offset:X}|{}", self.suffix)
+        }
+    }
+}
+
+fn layouts(signed: bool) -> Result<Vec<Layout>> {
+    let mut layouts = Vec::new();
+    for suffix in TYPES {
+        let mut encoding = Encoding::default();
+        for modifier in suffix.split('|') {
+            encoding.apply(modifier)?;
+        }
+        if encoding.signed != signed {
+            continue;
+        }
+        layouts.push(Layout {
+            encoding,
+            width: encoding.width.unwrap_or(1),
+            suffix: suffix.to_string(),
+            range: false,
+        });
+    }
+
+    if !signed {
+        for width in 1..=MAX_BCD_WIDTH {
+            for suffix in ["bcd", "bcd|be"] {
+                if width == 1 && suffix != "bcd" {
+                    continue;
+                }
+                let mut encoding = Encoding::default();
+                for modifier in suffix.split('|') {
+                    encoding.apply(modifier)?;
+                }
+                layouts.push(Layout {
+                    encoding,
+                    width,
+                    suffix: suffix.to_string(),
+                    range: true,
+                });
+            }
+        }
+    }
+
+    Ok(layouts)
+}
+
+// Like an emulator's cheat search: given saves and the value each is known to
+// hold, lists every offset and encoding that reads as that value in all of
+// them. Offsets are into the decompressed file, as in configs.
+pub fn find_offsets(saves: &[(&Path, i64)]) -> Result<Vec<Candidate>> {
+    if saves.is_empty() {
+        return Err(anyhow!("Need at least one save to search"));
+    }
+
+    let contents = saves
+        .iter()
+        .map(|(save, value)| Ok((container::read(save)?.data, *value)))
+        .collect::<Result<Vec<_>>>()?;
+    let len = contents.iter().map(|(data, _)| data.len()).min().unwrap();
+    let signed = contents.iter().any(|(_, value)| *value < 0);
+    let layouts = layouts(signed)?;
+
+    let mut candidates = Vec::new();
+    for offset in 0..len {
+        for layout in &layouts {
+            let Some(end) = offset.checked_add(layout.width).filter(|end| *end <= len) else {
+                continue;
+            };
+
+            let matches = contents.iter().all(|(data, value)| {
+                layout
+                    .encoding
+                    .decode(&data[offset..end])
+                    .is_ok_and(|decoded| decoded == *value)
+            });
+            if matches {
+                candidates.push(Candidate {
+                    offset,
+                    placeholder: layout.placeholder(offset),
+                });
+            }
+        }
+    }
+
+    Ok(candidates)
+}
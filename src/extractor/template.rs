@@ -0,0 +1,219 @@
+use super::{
+    container::Scope,
+    expr::{Expr, ParseError, Placeholder, Pointer},
+    parse_number,
+};
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use serde::Deserialize;
+use std::sync::OnceLock;
+use tracing::{debug, error};
+
+// Each line in the config is either a pattern or an object which repeats
+// lines for each slot, or shows a line or a nested list of lines when a
+// condition holds; unknown keys are rejected so a misspelled one isn't
+// silently ignored
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub(super) enum LineConfig {
+    Pattern(String),
+    Slots(SlotsLineConfig),
+    Conditional(ConditionalConfig),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub(super) struct SlotsLineConfig {
+    slots: SlotsConfig,
+    lines: Vec<LineConfig>,
+}
+
+// Needs show, lines, or both, optionally hiding lines whose values are all
+// zero
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub(super) struct ConditionalConfig {
+    #[serde(rename = "if")]
+    condition: Option<String>,
+    show: Option<String>,
+    #[serde(default)]
+    lines: Vec<LineConfig>,
+    #[serde(default)]
+    hide_zero: bool,
+}
+
+// A block repeated count times, stride bytes apart starting at base, where
+// offsets in lines are relative to the slot; a slot is skipped unless in_use
+// holds, and label is the slot's title
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub(super) struct SlotsConfig {
+    #[serde(default = "default_base")]
+    base: String,
+    stride: String,
+    count: usize,
+    in_use: Option<String>,
+    #[serde(default = "default_label")]
+    label: String,
+}
+
+fn default_base() -> String {
+    "0".to_string()
+}
+
+fn default_label() -> String {
+    "Slot {slot}".to_string()
+}
+
+// Conditions keep their source so that problems can be found in the config
+pub(super) enum Condition {
+    Always,
+    When(String, Expr),
+    Invalid(String, ParseError),
+}
+
+impl Condition {
+    fn parse(condition: Option<&String>, pointer: Pointer) -> Self {
+        match condition {
+            Some(condition) => match Expr::parse_condition(condition, pointer) {
+                Ok(expr) => Condition::When(condition.clone(), expr),
+                Err(e) => {
+                    error!("Invalid condition {condition:?} at {e}");
+                    Condition::Invalid(condition.clone(), e)
+                }
+            },
+            None => Condition::Always,
+        }
+    }
+
+    pub(super) fn holds(&self, scope: Scope) -> bool {
+        match self {
+            Condition::Always => true,
+            Condition::When(_, expr) => match expr.eval(scope) {
+                Ok(value) => value != 0,
+                Err(e) => {
+                    debug!("Could not evaluate condition {expr:?}: {e}");
+                    false
+                }
+            },
+            Condition::Invalid(..) => false,
+        }
+    }
+}
+
+pub(super) enum Line {
+    Template(Template),
+    Conditional {
+        condition: Condition,
+        hide_zero: bool,
+        lines: Vec<Line>,
+    },
+    Slots {
+        base: usize,
+        stride: usize,
+        count: usize,
+        in_use: Condition,
+        label: Template,
+        lines: Vec<Line>,
+    },
+}
+
+impl Line {
+    pub(super) fn parse(config: &LineConfig, pointer: Pointer) -> Result<Self> {
+        Ok(match config {
+            LineConfig::Pattern(pattern) => Line::Template(Template::parse(pattern, pointer)),
+            LineConfig::Slots(SlotsLineConfig { slots, lines }) => {
+                let address = |s: &str| {
+                    parse_number(s)
+                        .and_then(|n| usize::try_from(n).ok())
+                        .ok_or_else(|| anyhow!("Invalid slot offset {s:?}"))
+                };
+
+                Line::Slots {
+                    base: address(&slots.base)?,
+                    stride: address(&slots.stride)?,
+                    count: slots.count,
+                    in_use: Condition::parse(slots.in_use.as_ref(), pointer),
+                    label: Template::parse(&slots.label, pointer),
+                    lines: lines
+                        .iter()
+                        .map(|line| Line::parse(line, pointer))
+                        .collect::<Result<_>>()?,
+                }
+            }
+            LineConfig::Conditional(ConditionalConfig {
+                condition,
+                show,
+                lines,
+                hide_zero,
+            }) => {
+                if show.is_none() && lines.is_empty() {
+                    anyhow::bail!(
+                        "Line with condition {:?} has neither show nor lines",
+                        condition.as_deref().unwrap_or_default()
+                    );
+                }
+
+                let lines = show
+                    .iter()
+                    .map(|pattern| Ok(Line::Template(Template::parse(pattern, pointer))))
+                    .chain(lines.iter().map(|line| Line::parse(line, pointer)))
+                    .collect::<Result<_>>()?;
+
+                Line::Conditional {
+                    condition: Condition::parse(condition.as_ref(), pointer),
+                    hide_zero: *hide_zero,
+                    lines,
+                }
+            }
+        })
+    }
+}
+
+pub(super) enum Segment {
+    Text(String),
+    Placeholder(Placeholder),
+    Invalid(ParseError),
+}
+
+// A pattern split into literal text and parsed {...} placeholders
+pub(super) struct Template {
+    pub(super) pattern: String,
+    pub(super) segments: Vec<Segment>,
+}
+
+impl Template {
+    pub(super) fn parse(pattern: &str, pointer: Pointer) -> Self {
+        static RE: OnceLock<Regex> = OnceLock::new();
+        let re = RE.get_or_init(|| Regex::new(r"\{([^}]+)}").unwrap());
+
+        let mut segments = Vec::new();
+        let mut last = 0;
+        for caps in re.captures_iter(pattern) {
+            let (whole, inner) = (caps.get(0).unwrap(), caps.get(1).unwrap());
+            if whole.start() > last {
+                segments.push(Segment::Text(pattern[last..whole.start()].to_string()));
+            }
+            last = whole.end();
+
+            debug!("Found subpattern: {}", inner.as_str());
+            let column = pattern[..inner.start()].chars().count() + 1;
+            segments.push(match Placeholder::parse(inner.as_str(), column, pointer) {
+                Ok(placeholder) => Segment::Placeholder(placeholder),
+                Err(e) => {
+                    error!("Invalid pattern {pattern:?} at {e}");
+                    Segment::Invalid(e)
+                }
+            });
+        }
+
+        if last < pattern.len() {
+            segments.push(Segment::Text(pattern[last..].to_string()));
+        }
+
+        Self {
+            pattern: pattern.to_string(),
+            segments,
+        }
+    }
+}
@@ -1,6 +1,8 @@
+pub mod backup;
 pub mod cursor;
 pub mod extractor;
 pub mod internal;
 pub mod manager;
+pub mod restore;
 pub mod scene;
 pub mod ui;
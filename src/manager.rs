@@ -10,6 +10,8 @@ pub enum Action<T> {
     Continue,
     Push(Box<dyn Scene<T>>),
     Pop,
+    // Pop the current scene and push another in its place
+    Replace(Box<dyn Scene<T>>),
     Complete(T),
 }
 
@@ -53,6 +55,11 @@ impl<T> Manager<T> {
                     ui::Action::Continue
                 }
             }
+            Action::Replace(scene) => {
+                self.scenes.pop();
+                self.scenes.push(scene);
+                ui::Action::Continue
+            }
             Action::Complete(t) => ui::Action::Complete(t),
         }
     }
@@ -0,0 +1,390 @@
+use crate::{
+    backup::Backup,
+    internal::{files_for_directory, full_extension, remove_full_extension},
+};
+use anyhow::{Result, anyhow};
+use chrono::prelude::*;
+use itertools::Itertools;
+use regex::Regex;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, info};
+
+// Rules for a game come from <game>.restore next to it in the destination,
+// then default.restore in its directory or any directory above it up to the
+// destination; each key is taken from the nearest file that has it
+const DEFAULT_RULES: &str = "default.restore";
+
+// Which of the game's files in the destination are save data, which of those
+// to leave alone anyway, what to rename saves to as they're copied in, and
+// which of them a snapshot copies back into the library
+#[derive(Deserialize, Debug, Default)]
+struct RulesConfig {
+    save: Option<Vec<String>>,
+    keep: Option<Vec<String>>,
+    rename: Option<HashMap<String, String>>,
+    snapshot: Option<Vec<String>>,
+}
+
+pub struct Rules {
+    save: Regex,
+    keep: Option<Regex>,
+    rename: HashMap<String, String>,
+    snapshot: Regex,
+}
+
+// What restores did before rules were configurable
+impl Default for Rules {
+    fn default() -> Self {
+        Self::from_config(RulesConfig::default()).unwrap()
+    }
+}
+
+impl Rules {
+    pub fn load(game: &Path, root: &Path, destination: &Path) -> Result<Self> {
+        let target = target_stem(game, root, destination)?;
+
+        let mut files = vec![target.with_extension("restore")];
+        let mut directory = target.parent();
+        while let Some(d) = directory
+            && d.starts_with(destination)
+        {
+            files.push(d.join(DEFAULT_RULES));
+            directory = d.parent();
+        }
+
+        let mut config = RulesConfig::default();
+        for file in files.iter().filter(|f| f.exists()) {
+            debug!("Loading restore rules from {file:?}");
+            let contents = std::fs::read_to_string(file)?;
+            let rules: RulesConfig = serde_json::from_str(&contents)
+                .map_err(|e| anyhow!("Could not parse restore rules {file:?}: {e}"))?;
+            config.save = config.save.or(rules.save);
+            config.keep = config.keep.or(rules.keep);
+            config.rename = config.rename.or(rules.rename);
+            config.snapshot = config.snapshot.or(rules.snapshot);
+        }
+
+        Self::from_config(config)
+    }
+
+    // Patterns match the end of a file's full extension, so "state[0-9]*"
+    // covers foo.state and foo.state3
+    fn from_config(config: RulesConfig) -> Result<Self> {
+        let pattern = |extensions: Vec<String>| {
+            Regex::new(&format!("(?:{})$", extensions.join("|")))
+                .map_err(|e| anyhow!("Invalid extension pattern: {e}"))
+        };
+
+        let save = config.save.unwrap_or_else(|| {
+            ["srm", r"state[0-9]*", r"state\.auto", "sav", "rtc", "ldci"]
+                .map(String::from)
+                .to_vec()
+        });
+        let rename = config
+            .rename
+            .unwrap_or_else(|| HashMap::from([("state".to_string(), "state.auto".to_string())]));
+        // Files like .rtc sit alongside a save rather than being one, so on
+        // their own in the library they'd show up as saves that restore
+        // nothing useful
+        let snapshot = config
+            .snapshot
+            .unwrap_or_else(|| ["srm", r"state[0-9]*"].map(String::from).to_vec());
+
+        Ok(Self {
+            save: pattern(save)?,
+            keep: config
+                .keep
+                .filter(|k| !k.is_empty())
+                .map(pattern)
+                .transpose()?,
+            rename,
+            snapshot: pattern(snapshot)?,
+        })
+    }
+
+    fn is_save_data(&self, extension: &str) -> bool {
+        self.save.is_match(extension) && !self.is_kept(extension)
+    }
+
+    fn is_kept(&self, extension: &str) -> bool {
+        self.keep.as_ref().is_some_and(|k| k.is_match(extension))
+    }
+
+    // Matched against the extension the file gets in the library
+    fn is_snapshotted(&self, extension: &str) -> bool {
+        self.snapshot.is_match(self.library_extension(extension))
+    }
+
+    // By default RetroArch loads the auto state on launch, not the numbered ones
+    fn target_extension<'a>(&'a self, extension: &'a str) -> &'a str {
+        self.rename.get(extension).map_or(extension, String::as_str)
+    }
+
+    // The reverse of target_extension, for copying a save back into the library
+    fn library_extension<'a>(&'a self, extension: &'a str) -> &'a str {
+        self.rename
+            .iter()
+            .find(|(_, to)| *to == extension)
+            .map_or(extension, |(from, _)| from.as_str())
+    }
+}
+
+// What restoring a save would do, worked out before anything is touched so it
+// can be confirmed or printed first
+pub struct RestorePlan {
+    // The save in the library
+    pub save: PathBuf,
+
+    // Save data for the game already in the destination, which is moved aside
+    pub remove: Vec<PathBuf>,
+
+    // Where the save is copied to, named for what the emulator loads
+    pub target: PathBuf,
+
+    destination: PathBuf,
+}
+
+impl RestorePlan {
+    pub fn new(game: &Path, root: &Path, destination: &Path, save: &Path) -> Result<Self> {
+        let rules = Rules::load(game, root, destination)?;
+        let target = target_stem(game, root, destination)?;
+        let directory = target.parent().unwrap();
+        Self::from_files(
+            game,
+            root,
+            destination,
+            save,
+            &rules,
+            files_for_directory(directory),
+        )
+    }
+
+    // Plans against the given listing of the destination directory instead of
+    // reading it, so this makes no filesystem calls
+    pub fn from_files(
+        game: &Path,
+        root: &Path,
+        destination: &Path,
+        save: &Path,
+        rules: &Rules,
+        files: impl IntoIterator<Item = PathBuf>,
+    ) -> Result<Self> {
+        let mut target = target_stem(game, root, destination)?;
+        let files = files.into_iter().collect::<Vec<_>>();
+        let mut remove = save_data(game, rules, files.iter().cloned());
+
+        let extension =
+            full_extension(save).ok_or_else(|| anyhow!("Couldn't extract extension"))?;
+        let target_extension = rules.target_extension(extension);
+        target.set_extension(target_extension);
+
+        // Copying over a file moves it aside even when the rules don't count
+        // it as save data, so that has to be part of the plan too
+        if files.contains(&target) && !remove.contains(&target) {
+            if rules.is_kept(target_extension) {
+                anyhow::bail!(
+                    "Restoring would replace {:?}, which is kept",
+                    target.file_name().unwrap()
+                );
+            }
+            remove.push(target.clone());
+        }
+
+        Ok(Self {
+            save: save.to_path_buf(),
+            remove,
+            target,
+            destination: destination.to_path_buf(),
+        })
+    }
+
+    // Nothing is deleted or overwritten; it's moved aside so it can be undone
+    pub fn execute(&self, label: String) -> Result<Vec<String>> {
+        let mut results = vec![format!("Copied {label}")];
+
+        let mut backup = Backup::begin(&self.destination, label)?;
+
+        for file in &self.remove {
+            info!("Removing file {file:?}");
+            backup.move_aside(file)?;
+            results.push(format!("Removed {:?}", file.file_name().unwrap()));
+        }
+
+        backup.copy(&self.save, &self.target)?;
+
+        Ok(results)
+    }
+}
+
+// The game's path in the destination, without an extension
+fn target_stem(game: &Path, root: &Path, destination: &Path) -> Result<PathBuf> {
+    let mut target = destination.join(game.strip_prefix(root)?);
+    remove_full_extension(&mut target);
+    Ok(target)
+}
+
+// The game's files in the destination that the rules count as save data
+fn save_data(game: &Path, rules: &Rules, files: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    let prefix = game.file_name().unwrap();
+
+    let mut saves = Vec::new();
+    for file in files {
+        let mut stem = PathBuf::from(file.file_name().unwrap());
+        remove_full_extension(&mut stem);
+        if stem != prefix {
+            continue;
+        }
+
+        let Some(extension) = full_extension(&file) else {
+            continue;
+        };
+        if rules.is_save_data(extension) {
+            saves.push(file);
+        }
+    }
+    saves
+}
+
+// Copies the game's live save data from the destination into its library
+// directory, named for the current time like the saves already there, along
+// with the screenshot RetroArch keeps next to a savestate if there is one
+pub fn snapshot(game: &Path, root: &Path, destination: &Path) -> Result<Vec<String>> {
+    let rules = Rules::load(game, root, destination)?;
+    let target = target_stem(game, root, destination)?;
+    let directory = target.parent().unwrap();
+
+    let saves = save_data(game, &rules, files_for_directory(directory))
+        .into_iter()
+        .filter(|save| rules.is_snapshotted(full_extension(save).unwrap()))
+        .collect::<Vec<_>>();
+    if saves.is_empty() {
+        anyhow::bail!("No save data for {:?}", target.file_name().unwrap());
+    }
+
+    let timestamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut copies: Vec<(PathBuf, PathBuf)> = Vec::new();
+    // When both foo.state and foo.state.auto exist, they'd both be copied as
+    // .state, so only the one that restores rename back is kept
+    let keeps_extension = |save: &&PathBuf| {
+        let extension = full_extension(save).unwrap();
+        rules.library_extension(extension) == extension
+    };
+    for save in saves.iter().sorted_by_key(keeps_extension) {
+        let extension = full_extension(save).unwrap();
+        let copy = game.join(format!(
+            "{timestamp}.{}",
+            rules.library_extension(extension)
+        ));
+        if copies.iter().any(|(_, c)| *c == copy) {
+            debug!("Skipping {save:?}, which would also be copied to {copy:?}");
+            continue;
+        }
+        copies.push((save.clone(), copy));
+    }
+
+    let screenshot = copies
+        .iter()
+        .map(|(save, _)| PathBuf::from(format!("{}.png", save.display())))
+        .find(|png| png.exists());
+    if let Some(screenshot) = screenshot {
+        copies.push((screenshot, game.join(format!("{timestamp}.png"))));
+    }
+
+    if let Some((_, copy)) = copies.iter().find(|(_, copy)| copy.exists()) {
+        anyhow::bail!("{copy:?} already exists");
+    }
+
+    let mut results = vec![format!("Saved {timestamp}")];
+    for (source, copy) in copies {
+        info!("Copying {source:?} into {copy:?}");
+        std::fs::copy(&source, &copy)?;
+        results.push(format!("Copied {:?}", source.file_name().unwrap()));
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan_for(save: &str, files: &[&str]) -> RestorePlan {
+        let directory = Path::new("/dest/snes/English");
+        RestorePlan::from_files(
+            Path::new("/library/snes/English/Game"),
+            Path::new("/library"),
+            Path::new("/dest"),
+            &Path::new("/library/snes/English/Game").join(save),
+            &Rules::default(),
+            files.iter().map(|f| directory.join(f)),
+        )
+        .unwrap()
+    }
+
+    fn names(files: &[PathBuf]) -> Vec<&str> {
+        files
+            .iter()
+            .map(|f| f.file_name().unwrap().to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn other_games_are_excluded() {
+        let plan = plan_for(
+            "20240101-000000.srm",
+            &["Game.srm", "Game 2.srm", "Other.srm", "Other.state.auto"],
+        );
+        assert_eq!(names(&plan.remove), ["Game.srm"]);
+    }
+
+    #[test]
+    fn state_is_renamed_to_state_auto() {
+        let plan = plan_for("20240101-000000.state", &[]);
+        assert_eq!(plan.target, Path::new("/dest/snes/English/Game.state.auto"));
+
+        let plan = plan_for("20240101-000000.srm", &[]);
+        assert_eq!(plan.target, Path::new("/dest/snes/English/Game.srm"));
+    }
+
+    #[test]
+    fn save_data_is_picked_up() {
+        let plan = plan_for(
+            "20240101-000000.srm",
+            &["Game.ldci", "Game.rtc", "Game.state.auto", "Game.state3"],
+        );
+        assert_eq!(
+            names(&plan.remove),
+            ["Game.ldci", "Game.rtc", "Game.state.auto", "Game.state3"]
+        );
+    }
+
+    #[test]
+    fn other_files_are_left_alone() {
+        let plan = plan_for(
+            "20240101-000000.srm",
+            &[
+                "Game.extract",
+                "Game.restore",
+                "Game.sfc",
+                "Game.srm",
+                "Game.state.auto.png",
+            ],
+        );
+        assert_eq!(names(&plan.remove), ["Game.srm"]);
+    }
+
+    #[test]
+    fn snapshots_leave_out_companion_files() {
+        let rules = Rules::default();
+        for extension in ["srm", "state.auto", "state3"] {
+            assert!(rules.is_snapshotted(extension), "{extension}");
+        }
+        for extension in ["ldci", "rtc", "sav"] {
+            assert!(!rules.is_snapshotted(extension), "{extension}");
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use super::Scene;
+use super::message::draw_dialog;
+use crate::{manager::Action, ui::Button, ui::screen::Screen};
+use tracing::info;
+
+// Asks before doing something destructive. The d-pad moves between Yes and No,
+// starting on No; A picks, and B is the same as No. On Yes, the action's
+// result replaces this dialog, e.g. with a Message describing what happened.
+pub struct Confirm<T> {
+    messages: Vec<String>,
+    yes: bool,
+    on_confirm: Option<Box<dyn FnOnce() -> Action<T>>>,
+}
+
+impl<T> Confirm<T> {
+    pub fn new(messages: Vec<String>, on_confirm: impl FnOnce() -> Action<T> + 'static) -> Self {
+        info!("{messages:?}");
+
+        Self {
+            messages,
+            yes: false,
+            on_confirm: Some(Box::new(on_confirm)),
+        }
+    }
+}
+
+impl<T> Scene<T> for Confirm<T> {
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn pressed(&mut self, button: &Button) -> Option<Action<T>> {
+        match button {
+            Button::Left | Button::Right | Button::Up | Button::Down => {
+                self.yes = !self.yes;
+                Some(Action::Continue)
+            }
+            Button::A if self.yes => match self.on_confirm.take() {
+                Some(on_confirm) => Some(on_confirm()),
+                None => Some(Action::Pop),
+            },
+            Button::A | Button::B => Some(Action::Pop),
+            _ => Some(Action::Continue),
+        }
+    }
+
+    fn draw(&self, screen: &mut Screen) {
+        let options = if self.yes {
+            "[ Yes ]    No  "
+        } else {
+            "  Yes    [ No ]"
+        };
+
+        let mut lines = self.messages.clone();
+        lines.push(options.to_string());
+        draw_dialog(screen, &lines, false);
+    }
+}
@@ -1,7 +1,9 @@
+pub mod confirm;
 pub mod message;
 pub mod selectgame;
 pub mod selectsave;
 pub mod selectstring;
+pub mod undo;
 
 use crate::{
     manager::Action,
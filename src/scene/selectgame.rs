@@ -1,5 +1,6 @@
 use super::Scene;
 use super::selectsave::SelectSave;
+use super::undo::Undo;
 use crate::{
     internal::files_for_directory,
     manager::Action,
@@ -89,6 +90,7 @@ impl Scene<Operation> for SelectGame {
                     SelectSave::new(game.to_owned(), self.root.clone(), self.destination.clone());
                 Some(Action::Push(Box::new(scene)))
             }
+            Button::Y => Some(Action::Push(Box::new(Undo::new(self.destination.clone())))),
             Button::Start => {
                 if let Some(game) = self.current_game() {
                     Some(Action::Complete(Operation::ExecGame(game.to_owned())))
@@ -1,9 +1,12 @@
 use super::Scene;
+use super::confirm::Confirm;
 use super::message::Message;
+use super::undo::Undo;
 use crate::{
-    extractor::Extractor,
-    internal::{files_for_directory, full_extension, remove_full_extension},
+    extractor::{Extraction, Extractor, Validity},
+    internal::{files_for_directory, remove_full_extension},
     manager::Action,
+    restore::{RestorePlan, snapshot},
     scene::selectgame::Operation,
     ui::{
         Button,
@@ -11,18 +14,18 @@ use crate::{
         screen::{Color, FontSize, Rect, Screen},
     },
 };
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use chrono::prelude::*;
 use itertools::Itertools;
 use rand::Rng;
 use regex::Regex;
 use std::{
+    cell::RefCell,
     cmp::min,
-    collections::{HashMap, VecDeque},
+    collections::HashMap,
     path::{Path, PathBuf},
     sync::OnceLock,
 };
-use tracing::info;
 
 pub(super) struct SelectSave {
     game: PathBuf,
@@ -31,6 +34,14 @@ pub(super) struct SelectSave {
     list: List<(PathBuf, Option<PathBuf>)>,
     offset: usize,
     extractor: Option<Extractor>,
+
+    // Verifying a save means reading and maybe decompressing it, so each one
+    // is only checked once it's drawn and the result is kept
+    validity: RefCell<HashMap<PathBuf, Validity>>,
+
+    // Extracting can mean decompressing the save and running a script, so
+    // it's done once when the cursor lands on a save rather than every frame
+    extraction: Option<Extraction>,
 }
 
 fn saves_for_game(game: &Path) -> Vec<(PathBuf, Option<PathBuf>)> {
@@ -66,7 +77,6 @@ impl SelectSave {
     pub(super) fn new(game: PathBuf, root: PathBuf, destination: PathBuf) -> Self {
         let offset = rand::rng().random_range(100..999);
         let saves = saves_for_game(&game);
-        let list = List::new(saves, "Select a save".to_string());
 
         let extractor = match game.strip_prefix(&root) {
             Ok(prefix) => {
@@ -75,30 +85,37 @@ impl SelectSave {
                 remove_full_extension(&mut config);
                 config.set_extension("extract");
 
-                config
-                    .exists()
+                Extractor::exists(&config)
                     .then(|| Extractor::new(&config).ok())
                     .flatten()
             }
             Err(_) => None,
         };
 
-        Self {
+        let list = List::new(saves, "Select a save".to_string());
+
+        let mut scene = Self {
             game,
             list,
             root,
             destination,
             offset,
             extractor,
-        }
+            validity: RefCell::new(HashMap::new()),
+            extraction: None,
+        };
+        scene.refresh_extraction();
+        scene
     }
 
     fn label_for(&self, save: &Path) -> String {
-        save.strip_prefix(&self.game)
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_owned()
+        let label = save.strip_prefix(&self.game).unwrap().to_str().unwrap();
+
+        if self.validity_of(save) == Validity::Corrupt {
+            format!("[!] {label}")
+        } else {
+            label.to_owned()
+        }
     }
 
     fn timestamp_in_filename(&self, file: &Path) -> Option<DateTime<chrono::Local>> {
@@ -173,22 +190,77 @@ impl SelectSave {
         }
     }
 
-    fn extract_save(&self, save: &Path) -> Result<Vec<String>> {
+    fn extract_save(&self, save: &Path) -> Result<Option<Extraction>> {
         match &self.extractor {
-            Some(e) => e.extract(save),
-            None => Ok(Vec::new()),
+            Some(e) => e.extract(save).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Picks up saves added to the library, such as snapshots, keeping the
+    // cursor on the save that was selected
+    fn reload_saves(&mut self) {
+        let selected = self.list.current_item().map(|(save, _)| save.clone());
+        let saves = saves_for_game(&self.game);
+        self.list = List::new(saves, "Select a save".to_string());
+        if let Some(index) = self
+            .list
+            .items()
+            .iter()
+            .position(|(save, _)| Some(save) == selected.as_ref())
+        {
+            self.list.select(index);
         }
+        self.refresh_extraction();
     }
 
-    fn metadata_for_save(&self, save: &Path) -> Vec<String> {
+    fn refresh_extraction(&mut self) {
+        self.extraction = match self.list.current_item() {
+            Some((save, _)) => self.extract_save(save).ok().flatten(),
+            None => None,
+        };
+
+        // Extracting already verified the save, so there's no need to read it
+        // again for its label
+        if let Some((save, _)) = self.list.current_item()
+            && let Some(extraction) = &self.extraction
+        {
+            self.validity
+                .borrow_mut()
+                .insert(save.clone(), extraction.validity);
+        }
+    }
+
+    fn validity_of(&self, save: &Path) -> Validity {
+        let Some(extractor) = &self.extractor else {
+            return Validity::Unchecked;
+        };
+
+        *self
+            .validity
+            .borrow_mut()
+            .entry(save.to_path_buf())
+            .or_insert_with(|| extractor.validity(save).unwrap_or(Validity::Unchecked))
+    }
+
+    // Each line is paired with whether it's the heading of a group
+    fn metadata_for_save(&self, save: &Path) -> Vec<(String, bool)> {
         let mut metadata = Vec::new();
 
         if let Some(duration) = self.duration_since_save(save) {
-            metadata.push(duration);
+            metadata.push((duration, false));
         }
 
-        if let Ok(meta) = self.extract_save(save) {
-            metadata.extend(meta);
+        if let Some(extraction) = &self.extraction {
+            if extraction.validity == Validity::Corrupt {
+                metadata.push(("Checksum mismatch!".to_string(), false));
+            }
+            for group in &extraction.groups {
+                if let Some(title) = &group.title {
+                    metadata.push((title.clone(), true));
+                }
+                metadata.extend(group.lines.iter().map(|line| (line.text.clone(), false)));
+            }
         }
 
         metadata
@@ -198,72 +270,64 @@ impl SelectSave {
         self.list.current_item().unwrap()
     }
 
-    fn commit_save(&self) -> Result<Vec<String>> {
-        let mut results = VecDeque::new();
+    fn plan_restore(&self) -> Result<RestorePlan> {
         let (current_save, _) = self.current_save();
-        let label = self.label_for(current_save);
-
-        let mut destination = self.destination.clone();
-        destination.push(self.game.strip_prefix(&self.root)?);
-        remove_full_extension(&mut destination);
-
-        let directory = destination.parent().unwrap();
-
-        let prefix = self.game.file_name().unwrap();
+        RestorePlan::new(&self.game, &self.root, &self.destination, current_save)
+    }
 
-        static RE: OnceLock<Regex> = OnceLock::new();
-        let re = RE
-            .get_or_init(|| Regex::new(r"(?:srm|state[0-9]*|state\.auto|sav|rtc|ldci)$").unwrap());
-
-        for file in files_for_directory(directory) {
-            let basename = PathBuf::from(file.file_name().unwrap());
-            let mut stem = basename.clone();
-            remove_full_extension(&mut stem);
-            if stem != prefix {
-                continue;
-            }
+    fn confirm_restore(&self) -> Result<Confirm<Operation>> {
+        let (current_save, _) = self.current_save();
+        let label = self.label_for(current_save);
+        let plan = self.plan_restore()?;
 
-            let Some(extension) = full_extension(&file) else {
-                continue;
-            };
-            if re.is_match(extension) {
-                info!("Deleting file {file:?} for having extension {extension:?}");
-                std::fs::remove_file(&file)?;
-                results.push_back(format!("Removed {basename:?}"));
-            }
+        let mut messages = vec![format!("Restore {label}?")];
+        for file in &plan.remove {
+            messages.push(format!("Remove {:?}", file.file_name().unwrap()));
         }
+        messages.push(format!("Copy to {:?}", plan.target.file_name().unwrap()));
 
-        let extension = match full_extension(current_save) {
-            Some("state") => "state.auto",
-            Some(e) => e,
-            None => return Err(anyhow!("Couldn't extract extension")),
-        };
-        destination.set_extension(extension);
-        info!("Copying {current_save:?} into {destination:?}");
-        std::fs::copy(current_save, &destination)?;
-
-        results.push_front(format!("Copied {label}"));
-
-        Ok(Vec::from(results))
+        Ok(Confirm::new(messages, move || {
+            let scene = match plan.execute(label) {
+                Ok(messages) => Message::new(messages, false),
+                Err(e) => Message::new(vec![format!("Error updating saves"), e.to_string()], true),
+            };
+            Action::Replace(Box::new(scene))
+        }))
     }
 }
 
 impl Scene<Operation> for SelectSave {
     fn pressed(&mut self, button: &Button) -> Option<Action<Operation>> {
+        let index = self.list.cursor().index();
         if let Some(action) = self.list.handle_navigation(button) {
+            if self.list.cursor().index() != index {
+                self.refresh_extraction();
+            }
             return Some(action);
         }
 
         match button {
             Button::A => {
-                let scene = match self.commit_save() {
+                let scene: Box<dyn Scene<Operation>> = match self.confirm_restore() {
+                    Ok(confirm) => Box::new(confirm),
+                    Err(e) => Box::new(Message::new(
+                        vec![format!("Error planning restore"), e.to_string()],
+                        true,
+                    )),
+                };
+                Some(Action::Push(scene))
+            }
+            Button::X => {
+                let scene = match snapshot(&self.game, &self.root, &self.destination) {
                     Ok(messages) => Message::new(messages, false),
                     Err(e) => {
-                        Message::new(vec![format!("Error updating saves"), e.to_string()], true)
+                        Message::new(vec![format!("Error saving snapshot"), e.to_string()], true)
                     }
                 };
+                self.reload_saves();
                 Some(Action::Push(Box::new(scene)))
             }
+            Button::Y => Some(Action::Push(Box::new(Undo::new(self.destination.clone())))),
             Button::Start => Some(Action::Complete(Operation::ExecGame(self.game.clone()))),
             _ => Some(Action::Continue),
         }
@@ -296,8 +360,19 @@ impl Scene<Operation> for SelectSave {
             );
 
             let mut y = (preview_height + gap * 3) as i32;
-            for metadatum in metadata {
+            for (metadatum, heading) in metadata {
                 let (w, h) = screen.measure_text(FontSize::Body, &metadatum);
+                if heading {
+                    screen.draw_rect(
+                        Color::RGBA(36, 36, 36, 255),
+                        Rect::new(
+                            (screen_width - preview_width - gap * 2) as i32,
+                            y - PADDING as i32,
+                            preview_width,
+                            h + PADDING * 2 - 1,
+                        ),
+                    );
+                }
                 let w = min(w, preview_width + gap * 2);
                 let x = (screen_width - preview_width - gap * 2) as i32
                     + (preview_width as i32 - w as i32) / 2;
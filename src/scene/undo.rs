@@ -0,0 +1,114 @@
+use super::Scene;
+use super::confirm::Confirm;
+use super::message::Message;
+use crate::{
+    backup::Backup,
+    manager::Action,
+    ui::{Button, list::List, screen::Screen},
+};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+// Shows what the most recent restore did and reverts it with A, after
+// confirming; once undone, the restore before it is shown, so restores can be
+// undone one at a time
+pub struct Undo {
+    destination: PathBuf,
+
+    // Shared with the confirmation, which reloads it after undoing
+    loaded: Rc<RefCell<(Option<Backup>, List<String>)>>,
+}
+
+impl Undo {
+    pub fn new(destination: PathBuf) -> Self {
+        let loaded = Rc::new(RefCell::new(Self::load(&destination)));
+        Self {
+            destination,
+            loaded,
+        }
+    }
+
+    fn load(destination: &Path) -> (Option<Backup>, List<String>) {
+        let title = "Undo last restore".to_string();
+        let backup = match Backup::latest(destination) {
+            Ok(Some(backup)) => backup,
+            Ok(None) => return (None, List::new(vec!["Nothing to undo".to_string()], title)),
+            Err(e) => return (None, List::new(vec![format!("Error: {e}")], title)),
+        };
+
+        let items = [format!("Restored {} at {}", backup.label, backup.name())]
+            .into_iter()
+            .chain(backup.copied.iter().map(|f| format!("Remove {}", name(f))))
+            .chain(backup.moved.iter().map(|f| format!("Put back {}", name(f))))
+            .collect();
+
+        (Some(backup), List::new(items, title))
+    }
+
+    fn confirm_undo<T: 'static>(&self) -> Option<Confirm<T>> {
+        let loaded = self.loaded.borrow();
+        let backup = loaded.0.as_ref()?;
+
+        let mut messages = vec![format!("Undo restore of {}?", backup.label)];
+        for file in &backup.copied {
+            messages.push(format!("Keep {:?} in backups", name(file)));
+        }
+        for file in &backup.moved {
+            messages.push(format!("Put back {:?}", name(file)));
+        }
+
+        let destination = self.destination.clone();
+        let shared = Rc::clone(&self.loaded);
+        Some(Confirm::new(messages, move || {
+            let Some(backup) = shared.borrow_mut().0.take() else {
+                return Action::Pop;
+            };
+
+            let scene = match backup.undo() {
+                Ok(messages) => Message::new(
+                    [vec!["Undid restore".to_string()], messages].concat(),
+                    false,
+                ),
+                Err(e) => Message::new(
+                    vec!["Error undoing restore".to_string(), e.to_string()],
+                    true,
+                ),
+            };
+
+            *shared.borrow_mut() = Self::load(&destination);
+            Action::Replace(Box::new(scene))
+        }))
+    }
+}
+
+fn name(file: &Path) -> String {
+    file.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+impl<T: 'static> Scene<T> for Undo {
+    fn pressed(&mut self, button: &Button) -> Option<Action<T>> {
+        if let Some(action) = self.loaded.borrow_mut().1.handle_navigation(button) {
+            return Some(action);
+        }
+
+        match button {
+            Button::A => match self.confirm_undo() {
+                Some(confirm) => Some(Action::Push(Box::new(confirm))),
+                None => Some(Action::Continue),
+            },
+            _ => Some(Action::Continue),
+        }
+    }
+
+    fn draw(&self, screen: &mut Screen) {
+        self.loaded
+            .borrow()
+            .1
+            .draw(screen, false, false, |item| item.clone());
+    }
+}
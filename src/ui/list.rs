@@ -48,6 +48,14 @@ impl<T> List<T> {
         self.items.get(self.cursor.index())
     }
 
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.cursor.select(index);
+    }
+
     pub fn handle_navigation<U>(&mut self, button: &Button) -> Option<Action<U>> {
         match button {
             Button::B => Some(Action::Pop),